@@ -0,0 +1,114 @@
+use std::io;
+use std::fs;
+use std::path::Path;
+
+use crate::http::Result;
+
+use crate::http::codec::Framing;
+use crate::http::codec::BodyEncoder;
+
+const LENGTH_READ: usize = 8192usize;
+
+// Request body, either held in memory or streamed from a reader when it is sent.
+pub enum Body {
+    Empty,
+    Bytes(Vec<u8>),
+    // A reader without a known length is sent with chunked transfer coding.
+    Reader(Box<dyn io::Read>, Option<usize>),
+}
+
+impl Body {
+    pub fn from_reader(reader: Box<dyn io::Read>, length: Option<usize>) -> Self {
+        return Body::Reader(reader, length);
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = fs::File::open(path)?;
+        let length = file.metadata()?.len() as usize;
+
+        return Ok(Body::Reader(Box::new(file), Some(length)));
+    }
+
+    pub fn get_length(&self) -> Option<usize> {
+        return match self {
+            Body::Empty => Some(0usize),
+            Body::Bytes(bytes) => Some(bytes.len()),
+            Body::Reader(_, length) => *length,
+        };
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.get_length() == Some(0usize);
+    }
+
+    // Bytes of an in-memory body, None for a streamed one.
+    pub fn get_bytes(&self) -> Option<&[u8]> {
+        return match self {
+            Body::Empty => Some(&[]),
+            Body::Bytes(bytes) => Some(bytes),
+            Body::Reader(_, _) => None,
+        };
+    }
+
+    // Sends the body with the given framing. A streamed body can only be sent once, it is left empty afterwards.
+    pub(crate) fn write_to(&mut self, writer: &mut dyn io::Write, framing: Framing) -> Result<()> {
+        let mut encoder = BodyEncoder::new(framing);
+        let mut output = Vec::new();
+
+        match self {
+            Body::Empty => {}
+            Body::Bytes(bytes) => {
+                encoder.encode(bytes, &mut output)?;
+            }
+            Body::Reader(reader, _) => {
+                let mut buffer = vec![0u8; LENGTH_READ];
+
+                loop {
+                    let length = reader.read(&mut buffer)?;
+
+                    if length == 0usize {
+                        break;
+                    }
+
+                    encoder.encode(&buffer[..length], &mut output)?;
+
+                    writer.write_all(&output)?;
+                    output.clear();
+                }
+
+                *self = Body::Empty;
+            }
+        }
+
+        encoder.finish(&mut output)?;
+
+        writer.write_all(&output)?;
+        writer.flush()?;
+
+        return Ok(());
+    }
+}
+
+impl From<Vec<u8>> for Body {
+    fn from(bytes: Vec<u8>) -> Self {
+        return Body::Bytes(bytes);
+    }
+}
+
+impl From<&[u8]> for Body {
+    fn from(bytes: &[u8]) -> Self {
+        return Body::Bytes(bytes.to_vec());
+    }
+}
+
+impl From<String> for Body {
+    fn from(text: String) -> Self {
+        return Body::Bytes(text.into_bytes());
+    }
+}
+
+impl From<&str> for Body {
+    fn from(text: &str) -> Self {
+        return Body::Bytes(text.as_bytes().to_vec());
+    }
+}
@@ -0,0 +1,395 @@
+use std::io;
+use std::fs;
+use std::path::Path;
+
+use std::net::IpAddr;
+
+use std::sync::Mutex;
+
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use crate::http::Result;
+use crate::http::Url;
+
+use crate::http::date;
+
+const PREFIX_HTTP_ONLY: &str = "#HttpOnly_";
+
+// Cookie received in a "Set-Cookie" header (RFC 6265 5.2).
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Cookie {
+    name: String,
+    value: String,
+
+    domain: String,
+    // Set by a response without a "Domain" attribute, the cookie is then only sent back to that exact host.
+    host_only: bool,
+
+    path: String,
+
+    // None for a session cookie.
+    expires: Option<SystemTime>,
+
+    secure: bool,
+    http_only: bool,
+    same_site: Option<String>,
+}
+
+fn is_domain_match(host: &str, domain: &str) -> bool {
+    if host == domain {
+        return true;
+    }
+
+    return host.ends_with(domain)
+        && host[..host.len() - domain.len()].ends_with('.')
+        && host.parse::<IpAddr>().is_err();
+}
+
+fn is_path_match(path: &str, cookie_path: &str) -> bool {
+    if path == cookie_path {
+        return true;
+    }
+
+    return path.starts_with(cookie_path) && (cookie_path.ends_with('/') || path[cookie_path.len()..].starts_with('/'));
+}
+
+// The directory of the request path (RFC 6265 5.1.4).
+fn get_default_path(path: &str) -> &str {
+    return match path.rfind('/') {
+        None | Some(0usize) => "/",
+        Some(index) => &path[..index],
+    };
+}
+
+impl Cookie {
+    // Parses a "Set-Cookie" value received from `url`, None when it is malformed or not allowed to set for that host.
+    pub fn parse(header: &str, url: &Url) -> Option<Self> {
+        let mut parts = header.split(';');
+
+        let pair = parts.next()?;
+        let index = pair.find('=')?;
+
+        let name = pair[..index].trim();
+
+        if name.is_empty() {
+            return None;
+        }
+
+        let mut cookie = Self {
+            name: String::from(name),
+            value: String::from(pair[index + 1usize..].trim()),
+
+            domain: String::from(url.get_host()),
+            host_only: true,
+
+            path: String::from(get_default_path(url.get_path())),
+
+            expires: None,
+
+            secure: false,
+            http_only: false,
+            same_site: None,
+        };
+
+        let mut max_age = None;
+
+        for attribute in parts {
+            let (key, value) = match attribute.find('=') {
+                Some(index) => (attribute[..index].trim(), attribute[index + 1usize..].trim()),
+                None => (attribute.trim(), ""),
+            };
+
+            match key.to_ascii_lowercase().as_str() {
+                "expires" => {
+                    if let Some(expires) = date::parse_http_date(value) {
+                        cookie.expires = Some(expires);
+                    }
+                }
+                "max-age" => {
+                    if let Ok(seconds) = value.parse::<i64>() {
+                        max_age = Some(if seconds <= 0i64 {
+                            UNIX_EPOCH
+                        } else {
+                            SystemTime::now() + Duration::from_secs(seconds as u64)
+                        });
+                    }
+                }
+                "domain" => {
+                    let domain = value.trim_start_matches('.').to_ascii_lowercase();
+
+                    if !domain.is_empty() {
+                        // Without a public suffix list, at least refuse top-level domains.
+                        if !is_domain_match(url.get_host(), &domain) || (!domain.contains('.') && domain != url.get_host()) {
+                            return None;
+                        }
+
+                        cookie.domain = domain;
+                        cookie.host_only = false;
+                    }
+                }
+                "path" if value.starts_with('/') => cookie.path = String::from(value),
+                "secure" => cookie.secure = true,
+                "httponly" => cookie.http_only = true,
+                "samesite" => cookie.same_site = Some(String::from(value)),
+                _ => {}
+            }
+        }
+
+        // "Max-Age" wins over "Expires".
+        if max_age.is_some() {
+            cookie.expires = max_age;
+        }
+
+        return Some(cookie);
+    }
+
+    pub fn get_name(&self) -> &str {
+        return &self.name;
+    }
+
+    pub fn get_value(&self) -> &str {
+        return &self.value;
+    }
+
+    pub fn get_domain(&self) -> &str {
+        return &self.domain;
+    }
+
+    pub fn is_host_only(&self) -> bool {
+        return self.host_only;
+    }
+
+    pub fn get_path(&self) -> &str {
+        return &self.path;
+    }
+
+    pub fn get_expires(&self) -> Option<SystemTime> {
+        return self.expires;
+    }
+
+    pub fn is_secure(&self) -> bool {
+        return self.secure;
+    }
+
+    pub fn is_http_only(&self) -> bool {
+        return self.http_only;
+    }
+
+    pub fn get_same_site(&self) -> Option<&str> {
+        return self.same_site.as_deref();
+    }
+
+    pub fn is_expired(&self, now: SystemTime) -> bool {
+        return self.expires.is_some_and(|expires| expires <= now);
+    }
+
+    // Whether the cookie is sent along with a request to `url` (RFC 6265 5.4).
+    pub fn is_matching(&self, url: &Url) -> bool {
+        let domain_match = if self.host_only {
+            url.get_host() == self.domain
+        } else {
+            is_domain_match(url.get_host(), &self.domain)
+        };
+
+        return domain_match && is_path_match(url.get_path(), &self.path) && (!self.secure || url.get_scheme() == "https");
+    }
+
+    fn is_same(&self, other: &Cookie) -> bool {
+        return self.name == other.name && self.domain == other.domain && self.path == other.path;
+    }
+}
+
+// Cookies kept by `Client` across requests, shared between threads.
+pub struct CookieJar {
+    cookies: Mutex<Vec<Cookie>>,
+}
+
+impl CookieJar {
+    pub fn new() -> Self {
+        return Self {
+            cookies: Mutex::new(Vec::new()),
+        };
+    }
+
+    // Stores a cookie from a "Set-Cookie" header received from `url`, replacing the one with the same name, domain, and path.
+    // An already expired cookie only removes the one it replaces.
+    pub fn set_cookie(&self, url: &Url, header: &str) {
+        if let Some(cookie) = Cookie::parse(header, url) {
+            self.add(cookie);
+        }
+    }
+
+    pub fn add(&self, cookie: Cookie) {
+        let mut cookies = self.cookies.lock().unwrap_or_else(|error| error.into_inner());
+
+        cookies.retain(|stored| !stored.is_same(&cookie));
+
+        if !cookie.is_expired(SystemTime::now()) {
+            cookies.push(cookie);
+        }
+    }
+
+    // Value of the "Cookie" header for a request to `url`, cookies with longer paths first.
+    pub fn get_cookie_header(&self, url: &Url) -> Option<String> {
+        let now = SystemTime::now();
+
+        let mut cookies = self.cookies.lock().unwrap_or_else(|error| error.into_inner());
+
+        cookies.retain(|cookie| !cookie.is_expired(now));
+
+        let mut matching: Vec<&Cookie> = cookies.iter().filter(|cookie| cookie.is_matching(url)).collect();
+
+        if matching.is_empty() {
+            return None;
+        }
+
+        matching.sort_by_key(|cookie| std::cmp::Reverse(cookie.path.len()));
+
+        return Some(matching.iter().map(|cookie| format!("{}={}", cookie.name, cookie.value)).collect::<Vec<String>>().join("; "));
+    }
+
+    pub fn get_cookies(&self) -> Vec<Cookie> {
+        return self.cookies.lock().unwrap_or_else(|error| error.into_inner()).clone();
+    }
+
+    pub fn clear(&self) {
+        self.cookies.lock().unwrap_or_else(|error| error.into_inner()).clear();
+    }
+
+    // Writes the cookies in the Netscape "cookies.txt" format, also read by curl and wget.
+    // Session cookies are written with an expiry of 0, "SameSite" is not kept.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut output = String::from("# Netscape HTTP Cookie File\n");
+
+        for cookie in self.get_cookies() {
+            let expires = match cookie.expires {
+                Some(expires) => expires.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+                None => 0u64,
+            };
+
+            output.push_str(&format!("{}{}{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+                if cookie.http_only { PREFIX_HTTP_ONLY } else { "" },
+                if cookie.host_only { "" } else { "." },
+                cookie.domain,
+                if cookie.host_only { "FALSE" } else { "TRUE" },
+                cookie.path,
+                if cookie.secure { "TRUE" } else { "FALSE" },
+                expires,
+                cookie.name,
+                cookie.value));
+        }
+
+        fs::write(path, output)?;
+
+        return Ok(());
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let jar = Self::new();
+
+        for line in fs::read_to_string(path)?.lines() {
+            let (line, http_only) = match line.strip_prefix(PREFIX_HTTP_ONLY) {
+                Some(line) => (line, true),
+                None => (line, false),
+            };
+
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split('\t').collect();
+
+            if fields.len() != 7usize {
+                return Err(io::Error::from(io::ErrorKind::InvalidData).into());
+            }
+
+            let expires = match fields[4usize].parse::<u64>() {
+                Ok(0u64) => None,
+                Ok(seconds) => Some(UNIX_EPOCH + Duration::from_secs(seconds)),
+                Err(_) => return Err(io::Error::from(io::ErrorKind::InvalidData).into()),
+            };
+
+            jar.add(Cookie {
+                name: String::from(fields[5usize]),
+                value: String::from(fields[6usize]),
+
+                domain: fields[0usize].trim_start_matches('.').to_ascii_lowercase(),
+                host_only: fields[1usize] != "TRUE",
+
+                path: String::from(fields[2usize]),
+
+                expires,
+
+                secure: fields[3usize] == "TRUE",
+                http_only,
+                same_site: None,
+            });
+        }
+
+        return Ok(jar);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod assertions {
+        use std::time::SystemTime;
+
+        use crate::http::Url;
+
+        use super::super::Cookie;
+        use super::super::CookieJar;
+
+        #[test]
+        fn parse() {
+            let url = Url::parse("http://www.example.com/account/login").ok().unwrap();
+
+            let cookie = Cookie::parse("id=a3fWa; Domain=.Example.com; Path=/; Secure; HttpOnly; SameSite=Lax; Max-Age=60", &url).unwrap();
+
+            assert_eq!(cookie.get_name(), "id");
+            assert_eq!(cookie.get_value(), "a3fWa");
+            assert_eq!(cookie.get_domain(), "example.com");
+            assert!(!cookie.is_host_only());
+            assert_eq!(cookie.get_path(), "/");
+            assert!(cookie.is_secure() && cookie.is_http_only());
+            assert_eq!(cookie.get_same_site(), Some("Lax"));
+            assert!(cookie.get_expires().unwrap() > SystemTime::now());
+
+            let cookie = Cookie::parse("session=1; Expires=Wed, 21 Oct 2015 07:28:00 GMT", &url).unwrap();
+
+            assert!(cookie.is_host_only());
+            assert_eq!(cookie.get_path(), "/account");
+            assert!(cookie.is_expired(SystemTime::now()));
+
+            assert!(Cookie::parse("novalue", &url).is_none());
+            assert!(Cookie::parse("=value", &url).is_none());
+            assert!(Cookie::parse("id=1; Domain=other.com", &url).is_none());
+            assert!(Cookie::parse("id=1; Domain=com", &url).is_none());
+        }
+
+        #[test]
+        fn jar() {
+            let jar = CookieJar::new();
+            let url = Url::parse("http://www.example.com/account/login").ok().unwrap();
+
+            jar.set_cookie(&url, "a=1; Path=/");
+            jar.set_cookie(&url, "b=2");
+            jar.set_cookie(&url, "c=3; Domain=example.com; Path=/");
+            jar.set_cookie(&url, "d=4; Secure; Path=/");
+            jar.set_cookie(&url, "a=5; Path=/");
+
+            let header = |url: &str| jar.get_cookie_header(&Url::parse(url).ok().unwrap());
+
+            assert_eq!(header("http://www.example.com/account/settings").as_deref(), Some("b=2; c=3; a=5"));
+            assert_eq!(header("http://www.example.com/").as_deref(), Some("c=3; a=5"));
+            assert_eq!(header("http://api.example.com/account").as_deref(), Some("c=3"));
+            assert_eq!(header("http://example.org/"), None);
+
+            jar.set_cookie(&url, "c=; Domain=example.com; Path=/; Max-Age=0");
+
+            assert_eq!(header("http://api.example.com/"), None);
+        }
+    }
+}
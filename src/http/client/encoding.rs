@@ -0,0 +1,127 @@
+use std::io;
+
+use crate::http::Result;
+
+use super::response::RawBody;
+
+// Content codings the client can decode, by preference, as enabled by the cargo features of the same names.
+const CODINGS: &[(&str, bool)] = &[
+    ("br", cfg!(feature = "brotli")),
+    ("gzip", cfg!(feature = "gzip")),
+    ("deflate", cfg!(feature = "deflate")),
+];
+
+#[cfg(feature = "brotli")]
+const LENGTH_BROTLI_BUFFER: usize = 4096usize;
+
+// Value of the "Accept-Encoding" header, None when no content coding can be decoded.
+pub(crate) fn get_accept_encoding() -> Option<String> {
+    let codings: Vec<&str> = CODINGS.iter().filter(|(_, enabled)| *enabled).map(|(coding, _)| *coding).collect();
+
+    if codings.is_empty() {
+        return None;
+    }
+
+    return Some(codings.join(", "));
+}
+
+// Whether a "deflate" body starts with a zlib header (RFC 1950), as it should;
+// some servers send the raw deflate stream instead.
+#[cfg(feature = "deflate")]
+fn is_zlib(start: &[u8]) -> bool {
+    return start.len() == 2usize
+        && start[0usize] & 0x0Fu8 == 8u8
+        && ((start[0usize] as u16) << 8u32 | start[1usize] as u16).is_multiple_of(31u16);
+}
+
+// Decodes the content coding of a body as it is read from the connection.
+pub(crate) enum ContentDecoder {
+    Identity(RawBody),
+    #[cfg(feature = "gzip")]
+    Gzip(flate2::read::GzDecoder<RawBody>),
+    #[cfg(feature = "deflate")]
+    Deflate(flate2::read::ZlibDecoder<RawBody>),
+    #[cfg(feature = "deflate")]
+    DeflateRaw(flate2::read::DeflateDecoder<RawBody>),
+    #[cfg(feature = "brotli")]
+    // Boxed for its window buffers.
+    Brotli(Box<brotli_decompressor::Decompressor<RawBody>>),
+}
+
+impl ContentDecoder {
+    // Picks the decoder for the lowercase "Content-Encoding" `encoding`, also returning whether the body gets decoded.
+    // Empty bodies and codings that cannot be decoded, stacked ones included, are read as received.
+    pub(crate) fn new(mut raw: RawBody, encoding: Option<&str>) -> Result<(Self, bool)> {
+        let encoding = match encoding {
+            Some(encoding) if !raw.peek(1usize)?.is_empty() => encoding,
+            _ => return Ok((Self::Identity(raw), false)),
+        };
+
+        let decoder = match encoding {
+            #[cfg(feature = "gzip")]
+            "gzip" | "x-gzip" => Self::Gzip(flate2::read::GzDecoder::new(raw)),
+            #[cfg(feature = "deflate")]
+            "deflate" => {
+                if is_zlib(raw.peek(2usize)?) {
+                    Self::Deflate(flate2::read::ZlibDecoder::new(raw))
+                } else {
+                    Self::DeflateRaw(flate2::read::DeflateDecoder::new(raw))
+                }
+            }
+            #[cfg(feature = "brotli")]
+            "br" => Self::Brotli(Box::new(brotli_decompressor::Decompressor::new(raw, LENGTH_BROTLI_BUFFER))),
+            _ => Self::Identity(raw),
+        };
+
+        let decoded = !matches!(decoder, Self::Identity(_));
+
+        return Ok((decoder, decoded));
+    }
+
+    // The body as received, to read what a decoder left of it.
+    pub(crate) fn get_raw(&mut self) -> &mut RawBody {
+        return match self {
+            Self::Identity(raw) => raw,
+            #[cfg(feature = "gzip")]
+            Self::Gzip(decoder) => decoder.get_mut(),
+            #[cfg(feature = "deflate")]
+            Self::Deflate(decoder) => decoder.get_mut(),
+            #[cfg(feature = "deflate")]
+            Self::DeflateRaw(decoder) => decoder.get_mut(),
+            #[cfg(feature = "brotli")]
+            Self::Brotli(decoder) => decoder.get_mut(),
+        };
+    }
+}
+
+impl io::Read for ContentDecoder {
+    fn read(&mut self, output: &mut [u8]) -> io::Result<usize> {
+        return match self {
+            Self::Identity(raw) => raw.read(output),
+            #[cfg(feature = "gzip")]
+            Self::Gzip(decoder) => decoder.read(output),
+            #[cfg(feature = "deflate")]
+            Self::Deflate(decoder) => decoder.read(output),
+            #[cfg(feature = "deflate")]
+            Self::DeflateRaw(decoder) => decoder.read(output),
+            #[cfg(feature = "brotli")]
+            Self::Brotli(decoder) => decoder.read(output),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod assertions {
+        use super::super::get_accept_encoding;
+
+        #[test]
+        fn accept_encoding() {
+            let accept_encoding = get_accept_encoding();
+
+            assert_eq!(accept_encoding.as_deref().is_some_and(|codings| codings.contains("gzip")), cfg!(feature = "gzip"));
+            assert_eq!(accept_encoding.as_deref().is_some_and(|codings| codings.contains("br")), cfg!(feature = "brotli"));
+            assert_eq!(accept_encoding.is_none(), !cfg!(any(feature = "gzip", feature = "deflate", feature = "brotli")));
+        }
+    }
+}
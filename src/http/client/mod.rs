@@ -2,11 +2,25 @@ mod client;
 
 mod request;
 mod response;
+mod body;
+mod encoding;
+mod cookie;
+mod pool;
+mod proxy;
+mod redirect;
+mod retry;
 
 pub use self::client::Client;
 
 pub use self::request::Request;
+pub use self::request::RequestBuilder;
 pub use self::response::Response;
+pub use self::body::Body;
+pub use self::cookie::Cookie;
+pub use self::cookie::CookieJar;
+pub use self::proxy::Proxy;
+pub use self::redirect::RedirectPolicy;
+pub use self::retry::RetryPolicy;
 
 pub use super::Error;
 pub use super::Result;
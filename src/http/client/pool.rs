@@ -0,0 +1,161 @@
+use std::io;
+
+use std::collections::HashMap;
+
+use std::net::TcpStream;
+
+use std::sync::Mutex;
+
+use std::time::Duration;
+use std::time::Instant;
+
+struct Idle {
+    stream: TcpStream,
+
+    since: Instant,
+}
+
+// Keep-alive connections waiting to be reused, by "host:port".
+pub(crate) struct Pool {
+    idle: Mutex<HashMap<String, Vec<Idle>>>,
+
+    max_idle: usize,
+    idle_timeout: Duration,
+}
+
+// A connection closed by the server reads as EOF, and one with unexpected bytes pending cannot be used either.
+fn is_open(stream: &TcpStream) -> bool {
+    if stream.set_nonblocking(true).is_err() {
+        return false;
+    }
+
+    let open = match stream.peek(&mut [0u8; 1usize]) {
+        Ok(_) => false,
+        Err(error) => error.kind() == io::ErrorKind::WouldBlock,
+    };
+
+    return stream.set_nonblocking(false).is_ok() && open;
+}
+
+impl Pool {
+    pub(crate) fn new(max_idle: usize, idle_timeout: Duration) -> Self {
+        return Self {
+            idle: Mutex::new(HashMap::new()),
+
+            max_idle,
+            idle_timeout,
+        };
+    }
+
+    // Most recently used connection first, dropping the expired and closed ones on the way.
+    pub(crate) fn take(&self, key: &str) -> Option<TcpStream> {
+        let mut idle = self.idle.lock().unwrap_or_else(|error| error.into_inner());
+
+        let connections = idle.get_mut(key)?;
+
+        while let Some(connection) = connections.pop() {
+            if connection.since.elapsed() < self.idle_timeout && is_open(&connection.stream) {
+                return Some(connection.stream);
+            }
+        }
+
+        return None;
+    }
+
+    pub(crate) fn put(&self, key: String, stream: TcpStream) {
+        if self.max_idle == 0usize {
+            return;
+        }
+
+        let mut idle = self.idle.lock().unwrap_or_else(|error| error.into_inner());
+
+        let connections = idle.entry(key).or_default();
+        let idle_timeout = self.idle_timeout;
+
+        connections.retain(|connection| connection.since.elapsed() < idle_timeout);
+
+        if connections.len() >= self.max_idle {
+            connections.remove(0usize);
+        }
+
+        connections.push(Idle {
+            stream,
+
+            since: Instant::now(),
+        });
+    }
+
+    pub(crate) fn get_max_idle(&self) -> usize {
+        return self.max_idle;
+    }
+
+    pub(crate) fn get_idle_timeout(&self) -> Duration {
+        return self.idle_timeout;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod assertions {
+        use std::net::TcpListener;
+        use std::net::TcpStream;
+
+        use std::thread;
+
+        use std::time::Duration;
+
+        use super::super::Pool;
+
+        // Connections to a local listener, along with their server ends which keep them open.
+        fn connect(count: usize) -> (Vec<TcpStream>, Vec<TcpStream>) {
+            let listener = TcpListener::bind("127.0.0.1:0").ok().unwrap();
+            let address = listener.local_addr().ok().unwrap();
+
+            let clients: Vec<TcpStream> = (0usize..count).map(|_| TcpStream::connect(address).ok().unwrap()).collect();
+            let servers = (0usize..count).map(|_| listener.accept().ok().unwrap().0).collect();
+
+            return (clients, servers);
+        }
+
+        fn get_port(stream: &TcpStream) -> u16 {
+            return stream.local_addr().ok().unwrap().port();
+        }
+
+        #[test]
+        fn take_recent() {
+            let pool = Pool::new(2usize, Duration::from_secs(30u64));
+
+            let (clients, _servers) = connect(3usize);
+            let ports: Vec<u16> = clients.iter().map(get_port).collect();
+
+            for client in clients {
+                pool.put(String::from("localhost:80"), client);
+            }
+
+            // The oldest went once there were more than `max_idle`.
+            assert_eq!(pool.take("localhost:80").map(|stream| get_port(&stream)), Some(ports[2usize]));
+            assert_eq!(pool.take("localhost:80").map(|stream| get_port(&stream)), Some(ports[1usize]));
+            assert!(pool.take("localhost:80").is_none());
+            assert!(pool.take("localhost:8080").is_none());
+        }
+
+        #[test]
+        fn take_open() {
+            let pool = Pool::new(4usize, Duration::from_millis(50u64));
+
+            let (mut clients, mut servers) = connect(2usize);
+
+            pool.put(String::from("localhost:80"), clients.remove(0usize));
+            thread::sleep(Duration::from_millis(80u64));
+
+            assert!(pool.take("localhost:80").is_none());
+
+            // Closed by the server while idle.
+            pool.put(String::from("localhost:80"), clients.remove(0usize));
+            drop(servers.remove(1usize));
+            thread::sleep(Duration::from_millis(20u64));
+
+            assert!(pool.take("localhost:80").is_none());
+        }
+    }
+}
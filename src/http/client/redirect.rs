@@ -0,0 +1,170 @@
+use crate::http::Method;
+use crate::http::Status;
+use crate::http::Url;
+
+use super::Request;
+
+// Credentials that must not leak to another origin.
+const SENSITIVE_HEADERS: [&str; 3usize] = ["Authorization", "Cookie", "Proxy-Authorization"];
+
+// Fields describing the body, dropped along with it.
+const BODY_HEADERS: [&str; 5usize] = ["Content-Length", "Content-Type", "Content-Encoding", "Transfer-Encoding", "Expect"];
+
+// Which redirects `Client` follows.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum RedirectPolicy {
+    None,
+    // Follows up to this many redirects in a row.
+    Limited(usize),
+    // Same, but only redirects to the origin of the previous request.
+    SameOrigin(usize),
+}
+
+impl RedirectPolicy {
+    pub(crate) fn get_max_hops(&self) -> usize {
+        return match self {
+            RedirectPolicy::None => 0usize,
+            RedirectPolicy::Limited(max_hops) | RedirectPolicy::SameOrigin(max_hops) => *max_hops,
+        };
+    }
+
+    pub(crate) fn allows(&self, from: &Url, to: &Url) -> bool {
+        return match self {
+            RedirectPolicy::None => false,
+            RedirectPolicy::Limited(_) => true,
+            RedirectPolicy::SameOrigin(_) => from.is_same_origin(to),
+        };
+    }
+}
+
+pub(crate) fn is_redirect(status: &Status) -> bool {
+    return matches!(status,
+        Status::MovedPermanently | Status::Found | Status::SeeOther | Status::TemporaryRedirect | Status::PermanentRedirect);
+}
+
+// The request that follows a redirect from `from` to `to`, None when it cannot be sent again.
+// 303 always turns into a GET, and so do POSTs redirected by 301 or 302 as every browser does (RFC 7231 6.4).
+pub(crate) fn redirect(request: &Request, status: &Status, from: &Url, to: &Url) -> Option<Request> {
+    let method = *request.get_method();
+
+    let (method, keep_body) = match status {
+        Status::SeeOther => (if method == Method::HEAD { Method::HEAD } else { Method::GET }, false),
+        Status::MovedPermanently | Status::Found if method == Method::POST => (Method::GET, false),
+        _ => (method, true),
+    };
+
+    let mut next = Request::new();
+
+    next.set_method(method);
+    next.set_url(&to.to_string()).ok()?;
+
+    let same_origin = from.is_same_origin(to);
+
+    for (name, value) in request.get_headers().iter() {
+        let dropped = name.eq_ignore_ascii_case("Host")
+            || (!keep_body && BODY_HEADERS.iter().any(|header| name.eq_ignore_ascii_case(header)))
+            || (!same_origin && SENSITIVE_HEADERS.iter().any(|header| name.eq_ignore_ascii_case(header)));
+
+        if !dropped {
+            next.add_header(name, value);
+        }
+    }
+
+    if keep_body {
+        // A streamed body was consumed by the first request.
+        next.set_body(request.get_body().get_bytes()?.to_vec());
+    }
+
+    return Some(next);
+}
+
+#[cfg(test)]
+mod tests {
+    mod assertions {
+        use crate::http::Method;
+        use crate::http::Status;
+        use crate::http::Url;
+
+        use super::super::Request;
+        use super::super::RedirectPolicy;
+        use super::super::redirect;
+
+        fn get_request(method: Method) -> Request {
+            return Request::builder()
+                .method(method)
+                .url("http://example.com/form")
+                .header("Content-Type", "text/plain")
+                .header("Authorization", "Bearer secret")
+                .header("Cookie", "session=1")
+                .header("Proxy-Authorization", "Basic c2VjcmV0")
+                .header("X-Id", "7")
+                .body("hello")
+                .build().ok().unwrap();
+        }
+
+        fn follow(method: Method, status: Status, to: &str) -> Request {
+            let from = Url::parse("http://example.com/form").ok().unwrap();
+            let to = Url::parse(to).ok().unwrap();
+
+            return redirect(&get_request(method), &status, &from, &to).unwrap();
+        }
+
+        #[test]
+        fn method() {
+            let next = follow(Method::PUT, Status::SeeOther, "http://example.com/done");
+
+            assert_eq!(*next.get_method(), Method::GET);
+            assert!(next.get_body().is_empty());
+            assert_eq!(next.get_headers().get("Content-Type"), None);
+            assert_eq!(next.get_target(), "/done");
+
+            assert_eq!(*follow(Method::HEAD, Status::SeeOther, "http://example.com/done").get_method(), Method::HEAD);
+
+            for status in [Status::MovedPermanently, Status::Found] {
+                let next = follow(Method::POST, status, "http://example.com/done");
+
+                assert_eq!(*next.get_method(), Method::GET);
+                assert!(next.get_body().is_empty());
+
+                assert_eq!(*follow(Method::PUT, status, "http://example.com/done").get_method(), Method::PUT);
+            }
+
+            for status in [Status::TemporaryRedirect, Status::PermanentRedirect] {
+                let next = follow(Method::POST, status, "http://example.com/done");
+
+                assert_eq!(*next.get_method(), Method::POST);
+                assert_eq!(next.get_body().get_bytes(), Some(&b"hello"[..]));
+                assert_eq!(next.get_headers().get("Content-Type"), Some("text/plain"));
+            }
+        }
+
+        #[test]
+        fn credentials() {
+            let next = follow(Method::GET, Status::Found, "http://example.com/other");
+
+            assert_eq!(next.get_headers().get("Authorization"), Some("Bearer secret"));
+            assert_eq!(next.get_headers().get("Cookie"), Some("session=1"));
+
+            // Another port is another origin.
+            for to in ["http://example.org/other", "http://example.com:8080/other"] {
+                let next = follow(Method::GET, Status::Found, to);
+
+                assert_eq!(next.get_headers().get("Authorization"), None);
+                assert_eq!(next.get_headers().get("Cookie"), None);
+                assert_eq!(next.get_headers().get("Proxy-Authorization"), None);
+                assert_eq!(next.get_headers().get("X-Id"), Some("7"));
+            }
+        }
+
+        #[test]
+        fn policy() {
+            let from = Url::parse("http://example.com/").ok().unwrap();
+
+            assert!(RedirectPolicy::Limited(1usize).allows(&from, &Url::parse("http://example.org/").ok().unwrap()));
+            assert!(RedirectPolicy::SameOrigin(1usize).allows(&from, &Url::parse("http://example.com/other").ok().unwrap()));
+            assert!(!RedirectPolicy::SameOrigin(1usize).allows(&from, &Url::parse("http://example.org/").ok().unwrap()));
+            assert!(!RedirectPolicy::None.allows(&from, &from));
+            assert_eq!(RedirectPolicy::None.get_max_hops(), 0usize);
+        }
+    }
+}
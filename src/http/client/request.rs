@@ -1,11 +1,31 @@
+use std::io;
 use std::fmt;
 
+use crate::http::Result;
+use crate::http::Error;
+
 use crate::http::Method;
+use crate::http::Headers;
+use crate::http::Url;
+
+use crate::http::codec;
+use crate::http::codec::Framing;
+
+use super::Body;
+
+const DEFAULT_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
 
 pub struct Request {
     method: Method,
 
     target: String,
+
+    // Where the request is sent, when it is not the client's default address.
+    url: Option<Url>,
+
+    headers: Headers,
+
+    body: Body,
 }
 
 impl Request {
@@ -14,9 +34,19 @@ impl Request {
             method: Method::GET,
 
             target: String::from("/"),
+
+            url: None,
+
+            headers: Headers::new(),
+
+            body: Body::Empty,
         };
     }
 
+    pub fn builder() -> RequestBuilder {
+        return RequestBuilder::new();
+    }
+
     pub fn set_method(&mut self, method: Method) {
         self.method = method;
     }
@@ -48,10 +78,309 @@ impl Request {
     pub fn get_target(&self) -> &str {
         return &self.target;
     }
+
+    // Sends the request to the URL's host, with the URL's path and query as target.
+    pub fn set_url(&mut self, url: &str) -> Result<()> {
+        let url = Url::parse(url)?;
+
+        // As given, since "/dir/" and "/dir" are different resources to most servers.
+        self.target = String::from(url.get_target());
+        self.url = Some(url);
+
+        return Ok(());
+    }
+
+    pub fn get_url(&self) -> Option<&Url> {
+        return self.url.as_ref();
+    }
+
+    pub fn set_header(&mut self, name: &str, value: &str) {
+        self.headers.set(name, value);
+    }
+
+    pub fn get_headers(&self) -> &Headers {
+        return &self.headers;
+    }
+
+    pub fn add_header(&mut self, name: &str, value: &str) {
+        self.headers.add(name, value);
+    }
+
+    pub fn remove_header(&mut self, name: &str) {
+        self.headers.remove(name);
+    }
+
+    pub fn set_body<B: Into<Body>>(&mut self, body: B) {
+        self.body = body.into();
+    }
+
+    pub fn get_body(&self) -> &Body {
+        return &self.body;
+    }
+
+    // Framing announced by the user's own headers if any, otherwise derived from the body.
+    pub fn get_framing(&self) -> Framing {
+        if let Some(encoding) = self.headers.get("Transfer-Encoding") {
+            if encoding.to_ascii_lowercase().contains("chunked") {
+                return Framing::Chunked;
+            }
+        }
+
+        if let Some(length) = self.headers.get("Content-Length") {
+            if let Ok(length) = length.trim().parse::<usize>() {
+                return Framing::Length(length);
+            }
+        }
+
+        return match self.body.get_length() {
+            Some(length) => Framing::Length(length),
+            None => Framing::Chunked,
+        };
+    }
+
+    // Asks the server to confirm with "100 Continue" before the body is sent.
+    pub fn set_expect_continue(&mut self, expect: bool) {
+        if expect {
+            self.headers.set("Expect", "100-continue");
+        } else {
+            self.headers.remove("Expect");
+        }
+    }
+
+    pub fn encode_head(&self, output: &mut Vec<u8>) {
+        codec::encode_request_head(output, &self.method, &self.target, &self.get_head_headers(&Headers::new()));
+    }
+
+    // Same, with `defaults` sent first for the fields the user did not set, such as "Host".
+    // The target is given apart since it takes the absolute form when sent to a proxy.
+    pub(crate) fn encode_head_with(&self, output: &mut Vec<u8>, target: &str, defaults: &Headers) {
+        codec::encode_request_head(output, &self.method, target, &self.get_head_headers(defaults));
+    }
+
+    pub(crate) fn write_body(&mut self, writer: &mut dyn io::Write) -> Result<()> {
+        let framing = self.get_framing();
+
+        return self.body.write_to(writer, framing);
+    }
+
+    // The user's headers completed with the ones every request needs, unless the user set them already.
+    fn get_head_headers(&self, defaults: &Headers) -> Headers {
+        let mut headers = Headers::new();
+
+        for (name, value) in defaults.iter() {
+            if !self.headers.contains(name) {
+                headers.add(name, value);
+            }
+        }
+
+        for (name, value) in self.headers.iter() {
+            headers.add(name, value);
+        }
+
+        if !headers.contains("User-Agent") {
+            headers.add("User-Agent", DEFAULT_USER_AGENT);
+        }
+
+        if !headers.contains("Content-Length") && !headers.contains("Transfer-Encoding") {
+            match self.get_framing() {
+                Framing::Length(0usize) => {
+                    // An empty body is only worth announcing for methods that expect one (RFC 7230 3.3.2).
+                    if let Method::POST | Method::PUT | Method::PATCH = self.method {
+                        headers.add("Content-Length", "0");
+                    }
+                }
+                Framing::Length(length) => {
+                    headers.add("Content-Length", &length.to_string());
+                }
+                Framing::Chunked | Framing::Close => {
+                    headers.add("Transfer-Encoding", "chunked");
+                }
+            }
+        }
+
+        return headers;
+    }
+
+    // Whether the request asks for the connection to be closed after the response.
+    pub fn is_closing(&self) -> bool {
+        return match self.headers.get("Connection") {
+            Some(connection) => connection.split(',').any(|option| option.trim().eq_ignore_ascii_case("close")),
+            None => false,
+        };
+    }
+
+    pub fn is_expecting_continue(&self) -> bool {
+        return match self.headers.get("Expect") {
+            Some(expect) => expect.eq_ignore_ascii_case("100-continue"),
+            None => false,
+        };
+    }
 }
 
 impl fmt::Display for Request {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        return formatter.write_fmt(format_args!("{} {} HTTP/1.1\r\n\r\n", self.method, self.target));
+        let mut head = Vec::new();
+        self.encode_head(&mut head);
+
+        return formatter.write_str(&String::from_utf8_lossy(&head));
+    }
+}
+
+// Fluent construction of a `Request`, errors are reported once by `build`.
+pub struct RequestBuilder {
+    request: Request,
+
+    error: Option<Error>,
+}
+
+impl RequestBuilder {
+    pub fn new() -> Self {
+        return Self {
+            request: Request::new(),
+
+            error: None,
+        };
+    }
+
+    pub fn method(mut self, method: Method) -> Self {
+        self.request.set_method(method);
+
+        return self;
+    }
+
+    pub fn target(mut self, target: &str) -> Self {
+        if let Err(error) = self.request.set_target(target.as_bytes()) {
+            self.error.get_or_insert(error);
+        }
+
+        return self;
+    }
+
+    pub fn url(mut self, url: &str) -> Self {
+        if let Err(error) = self.request.set_url(url) {
+            self.error.get_or_insert(error);
+        }
+
+        return self;
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.request.add_header(name, value);
+
+        return self;
+    }
+
+    pub fn body<B: Into<Body>>(mut self, body: B) -> Self {
+        self.request.set_body(body);
+
+        return self;
+    }
+
+    pub fn file<P: AsRef<std::path::Path>>(mut self, path: P) -> Self {
+        match Body::from_file(path) {
+            Ok(body) => self.request.set_body(body),
+            Err(error) => {
+                self.error.get_or_insert(error);
+            }
+        }
+
+        return self;
+    }
+
+    pub fn reader(mut self, reader: Box<dyn io::Read>, length: Option<usize>) -> Self {
+        self.request.set_body(Body::from_reader(reader, length));
+
+        return self;
+    }
+
+    pub fn expect_continue(mut self, expect: bool) -> Self {
+        self.request.set_expect_continue(expect);
+
+        return self;
+    }
+
+    pub fn build(self) -> Result<Request> {
+        return match self.error {
+            Some(error) => Err(error),
+            None => Ok(self.request),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod assertions {
+        use std::io;
+
+        use crate::http::Error;
+        use crate::http::Method;
+
+        use crate::http::codec::Framing;
+
+        use super::super::Body;
+        use super::super::Request;
+
+        fn get_head(request: &Request) -> String {
+            let mut head = Vec::new();
+            request.encode_head(&mut head);
+
+            return String::from_utf8_lossy(&head).into_owned();
+        }
+
+        #[test]
+        fn builder() {
+            // The first error is the one reported, once everything else was set.
+            let result = Request::builder().target("lorem").url("http://[::1/").header("X-Id", "7").build();
+
+            assert!(matches!(result, Err(Error::InvalidPath)));
+
+            let request = Request::builder().method(Method::PUT).url("http://example.com:8080/a/b?c=d").header("X-Id", "7").body("hello").build().ok().unwrap();
+
+            assert_eq!(*request.get_method(), Method::PUT);
+            assert_eq!(request.get_target(), "/a/b?c=d");
+            assert_eq!(request.get_url().map(|url| url.get_port()), Some(8080u16));
+            assert_eq!(request.get_headers().get("X-Id"), Some("7"));
+            assert_eq!(request.get_body().get_bytes(), Some(&b"hello"[..]));
+        }
+
+        #[test]
+        fn framing() {
+            let request = Request::builder().method(Method::POST).body("hello").header("Content-Length", "3").build().ok().unwrap();
+
+            assert_eq!(request.get_framing(), Framing::Length(3usize));
+            assert!(get_head(&request).contains("Content-Length: 3\r\n"));
+            assert!(!get_head(&request).contains("Content-Length: 5"));
+
+            let request = Request::builder().method(Method::POST).body("hello").header("Transfer-Encoding", "chunked").build().ok().unwrap();
+
+            assert_eq!(request.get_framing(), Framing::Chunked);
+            assert!(!get_head(&request).contains("Content-Length"));
+
+            // Only methods expecting a body announce an empty one.
+            for (method, announced) in [(Method::POST, true), (Method::PUT, true), (Method::PATCH, true), (Method::GET, false), (Method::DELETE, false)] {
+                let request = Request::builder().method(method).build().ok().unwrap();
+
+                assert_eq!(get_head(&request).contains("Content-Length: 0\r\n"), announced);
+            }
+        }
+
+        #[test]
+        fn write_body() {
+            let reader: Box<dyn io::Read> = Box::new(&b"hello, world"[..]);
+            let mut request = Request::builder().method(Method::POST).reader(reader, None).build().ok().unwrap();
+
+            assert!(get_head(&request).contains("Transfer-Encoding: chunked\r\n"));
+
+            let mut output = Vec::new();
+            assert!(request.write_body(&mut output).is_ok());
+
+            assert_eq!(output, b"C\r\nhello, world\r\n0\r\n\r\n");
+            assert!(matches!(request.get_body(), Body::Empty));
+
+            // A reader shorter than its announced length fails rather than sending a truncated body.
+            let mut body = Body::from_reader(Box::new(&b"hel"[..]), Some(5usize));
+
+            assert!(body.write_to(&mut Vec::new(), Framing::Length(5usize)).is_err());
+        }
     }
 }
@@ -1,67 +1,522 @@
 use std::io;
+use std::io::Read;
+use std::fs;
 use std::fmt;
+use std::path::Path;
+
+use std::net::TcpStream;
+
+use std::sync::Arc;
 
 use crate::http::Result;
 use crate::http::Error;
 
 use crate::http::Status;
+use crate::http::Headers;
+use crate::http::Limits;
+use crate::http::Url;
 
-const MAX_HEADER_LENGTH: usize = 4096usize;
+use crate::http::codec;
+use crate::http::codec::Event;
+use crate::http::codec::ParseMode;
+use crate::http::codec::ResponseDecoder;
 
-pub struct Response {
-    buffer: [u8; MAX_HEADER_LENGTH],
-    buffer_length: usize,
-    buffer_finger: usize,
+use super::pool::Pool;
+use super::encoding::ContentDecoder;
 
-    status: Status,
+const LENGTH_READ: usize = 4096usize;
+
+// Connection the rest of a response body is read from.
+pub(crate) struct Connection {
+    stream: TcpStream,
+
+    decoder: ResponseDecoder,
+
+    // Where the connection goes back once the body was read to the end, None when it cannot be reused.
+    pool: Option<(Arc<Pool>, String)>,
 }
 
-impl Response {
-    fn new() -> Self {
+impl Connection {
+    pub(crate) fn new(stream: TcpStream, decoder: ResponseDecoder, pool: Option<(Arc<Pool>, String)>) -> Self {
         return Self {
-            buffer: [0u8; MAX_HEADER_LENGTH],
-            buffer_length: 0usize,
-            buffer_finger: 0usize,
+            stream,
+
+            decoder,
 
-            status: Status::OK,
+            pool,
         };
     }
+}
 
-    pub fn parse(reader: &mut dyn io::Read) -> Result<Self> {
-        let mut response: Self = Self::new();
-        response.buffer_length = reader.read(&mut response.buffer)?;
+// Body as the server sent it, with only its framing decoded, read from the connection as it arrives.
+pub(crate) struct RawBody {
+    pending: Vec<u8>,
+    finger: usize,
 
-        if response.buffer_finger + 8usize + 1usize >= response.buffer_length {
-            return Err(Error::BadResponse);
+    connection: Option<Connection>,
+}
+
+impl RawBody {
+    fn new(connection: Connection) -> Self {
+        return Self {
+            pending: Vec::new(),
+            finger: 0usize,
+
+            connection: Some(connection),
+        };
+    }
+
+    // Receives the next piece of the body into `pending`, false once the body is complete.
+    // The connection then goes back to the pool if it can be reused.
+    fn receive(&mut self) -> Result<bool> {
+        let connection = match &mut self.connection {
+            Some(connection) => connection,
+            None => return Ok(false),
+        };
+
+        if read_data(&mut connection.decoder, &mut connection.stream, &mut self.pending)? {
+            return Ok(true);
         }
 
-        if !response.buffer.starts_with(b"HTTP/1.1 ") {
-            return Err(Error::BadResponse);
+        if let Some(connection) = self.connection.take() {
+            let reusable = !connection.decoder.is_eof() && connection.decoder.get_buffered().is_empty();
+
+            if let (Some((pool, key)), true) = (connection.pool, reusable) {
+                pool.put(key, connection.stream);
+            }
         }
 
-        response.buffer_finger += 8usize + 1usize;
+        return Ok(false);
+    }
+
+    // The next `length` bytes, fewer if the body is shorter, without consuming them.
+    pub(crate) fn peek(&mut self, length: usize) -> Result<&[u8]> {
+        while self.pending.len() - self.finger < length && self.receive()? {}
 
-        if response.buffer_finger + 3usize >= response.buffer_length {
-            return Err(Error::BadResponse);
+        let end = std::cmp::min(self.pending.len(), self.finger + length);
+
+        return Ok(&self.pending[self.finger..end]);
+    }
+
+    // Receives and drops what is left of the body, so that the connection can be reused.
+    pub(crate) fn drain(&mut self) -> Result<()> {
+        loop {
+            self.pending.clear();
+            self.finger = 0usize;
+
+            if !self.receive()? {
+                return Ok(());
+            }
         }
+    }
+}
 
-        response.status = match Status::from_code_text(&response.buffer[response.buffer_finger..response.buffer_finger + 3usize]) {
-            Some(status) => status,
-            None => return Err(Error::BadResponse),
-        };
+impl io::Read for RawBody {
+    fn read(&mut self, output: &mut [u8]) -> io::Result<usize> {
+        if output.is_empty() {
+            return Ok(0usize);
+        }
+
+        if self.finger == self.pending.len() {
+            self.pending.clear();
+            self.finger = 0usize;
+
+            if !self.receive().map_err(into_io_error)? {
+                return Ok(0usize);
+            }
+        }
+
+        let length = std::cmp::min(output.len(), self.pending.len() - self.finger);
+
+        output[..length].copy_from_slice(&self.pending[self.finger..self.finger + length]);
+        self.finger += length;
+
+        return Ok(length);
+    }
+}
+
+pub struct Response {
+    version: String,
+
+    status: Status,
+
+    reason: String,
+
+    headers: Headers,
 
-        response.buffer_finger += 3usize;
+    // Body read so far, `finger` marks how much of it was consumed through `io::Read`.
+    body: Vec<u8>,
+    finger: usize,
+
+    // Set while the body is still being received.
+    source: Option<ContentDecoder>,
+
+    // Whether the body is decoded from its "Content-Encoding".
+    decoded: bool,
+
+    // URLs redirected to before this response, in order.
+    redirects: Vec<Url>,
+}
+
+fn into_io_error(error: Error) -> io::Error {
+    return match error {
+        Error::InternalError(source) => match source.downcast::<io::Error>() {
+            Ok(error) => *error,
+            Err(source) => io::Error::other(source.to_string()),
+        },
+        error => io::Error::new(io::ErrorKind::InvalidData, error.to_string()),
+    };
+}
+
+// Appends the next piece of body data to `body`, false once the body ended.
+fn read_data(decoder: &mut ResponseDecoder, reader: &mut dyn io::Read, body: &mut Vec<u8>) -> Result<bool> {
+    loop {
+        match decoder.poll()? {
+            Some(Event::Data(data)) => {
+                body.extend_from_slice(data);
+
+                return Ok(true);
+            }
+            Some(Event::End) => return Ok(false),
+            Some(Event::Head(_)) => return Err(Error::BadResponse),
+            None => {
+                let length = reader.read(decoder.prepare(LENGTH_READ))?;
+
+                if length == 0usize {
+                    decoder.feed_eof();
+                } else {
+                    decoder.commit(length);
+                }
+            }
+        }
+    }
+}
+
+impl Response {
+    pub fn parse(reader: &mut dyn io::Read) -> Result<Self> {
+        return Self::parse_with(reader, &Limits::new());
+    }
+
+    pub fn parse_with(reader: &mut dyn io::Read, limits: &Limits) -> Result<Self> {
+        let mut decoder = ResponseDecoder::new(ParseMode::Strict, limits);
+
+        let mut response = Self::read_head(&mut decoder, reader)?;
+
+        while read_data(&mut decoder, reader, &mut response.body)? {}
 
         return Ok(response);
     }
 
+    // Reads the head of the next response, which may be an interim (1xx) response.
+    // Its body is left to be polled from the decoder, except for interim responses which never have one.
+    pub(crate) fn read_head(decoder: &mut ResponseDecoder, reader: &mut dyn io::Read) -> Result<Self> {
+        loop {
+            if let Some(event) = decoder.poll()? {
+                let response = match event {
+                    Event::Head(head) => Self {
+                        version: String::from(head.get_version()),
+
+                        status: *head.get_status(),
+
+                        reason: String::from(head.get_reason()),
+
+                        headers: head.to_headers(),
+
+                        body: Vec::new(),
+                        finger: 0usize,
+
+                        source: None,
+
+                        decoded: false,
+
+                        redirects: Vec::new(),
+                    },
+                    _ => return Err(Error::BadResponse),
+                };
+
+                // A switching protocols response ends the exchange, its empty body is left to be read like a final one.
+                if response.is_interim() {
+                    while read_data(decoder, reader, &mut Vec::new())? {}
+                }
+
+                return Ok(response);
+            }
+
+            let length = reader.read(decoder.prepare(LENGTH_READ))?;
+
+            if length == 0usize {
+                // Closed before anything was answered, which is how a stale keep-alive connection shows.
+                if decoder.get_buffered().is_empty() {
+                    return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+                }
+
+                decoder.feed_eof();
+            } else {
+                decoder.commit(length);
+            }
+        }
+    }
+
+    // Reads heads until the final one, skipping interim (1xx) responses.
+    pub(crate) fn read_final_head(decoder: &mut ResponseDecoder, reader: &mut dyn io::Read) -> Result<Self> {
+        loop {
+            let response = Self::read_head(decoder, reader)?;
+
+            if !response.is_interim() {
+                return Ok(response);
+            }
+        }
+    }
+
+    // Hands over the connection the body is still to be read from.
+    // With `decode`, a body in a supported content coding is decoded as it is read.
+    pub(crate) fn set_connection(&mut self, connection: Connection, decode: bool) -> Result<()> {
+        let encoding = match self.headers.get("Content-Encoding") {
+            Some(encoding) if decode => Some(encoding.trim().to_ascii_lowercase()),
+            _ => None,
+        };
+
+        let (source, decoded) = ContentDecoder::new(RawBody::new(connection), encoding.as_deref())?;
+
+        self.source = Some(source);
+        self.decoded = decoded;
+
+        return Ok(());
+    }
+
+    pub fn get_version(&self) -> &str {
+        return &self.version;
+    }
+
     pub fn get_status(&self) -> &Status {
         return &self.status;
     }
+
+    pub fn get_reason(&self) -> &str {
+        return &self.reason;
+    }
+
+    pub fn get_headers(&self) -> &Headers {
+        return &self.headers;
+    }
+
+    pub fn get_content_length(&self) -> Option<usize> {
+        return match self.headers.get("Content-Length") {
+            Some(length) => length.parse::<usize>().ok(),
+            None => None,
+        };
+    }
+
+    // Whether the server lets the connection be reused: HTTP/1.1 does by default, HTTP/1.0 only when asked to.
+    pub fn is_keep_alive(&self) -> bool {
+        let mut options = self.headers.get_all("Connection").flat_map(|connection| connection.split(',')).map(|option| option.trim());
+
+        if self.version == "HTTP/1.0" {
+            return options.any(|option| option.eq_ignore_ascii_case("keep-alive"));
+        }
+
+        return !options.any(|option| option.eq_ignore_ascii_case("close"));
+    }
+
+    // Coding the server applied to the body, which is still readable here when the body was decoded.
+    pub fn get_content_encoding(&self) -> Option<&str> {
+        return self.headers.get("Content-Encoding");
+    }
+
+    // Whether the body read is decoded from its content coding, otherwise it is given as received.
+    pub fn is_decoded(&self) -> bool {
+        return self.decoded;
+    }
+
+    // Body held in memory: all of it for responses returned by `Client::send`,
+    // and for streamed ones what was not read yet when `buffer` was called.
+    pub fn get_body(&self) -> &[u8] {
+        return &self.body;
+    }
+
+    pub fn into_body(self) -> Vec<u8> {
+        return self.body;
+    }
+
+    // Whether part of the body has still to be received.
+    pub fn is_streaming(&self) -> bool {
+        return self.source.is_some();
+    }
+
+    // Receives the rest of the body into memory.
+    pub fn buffer(&mut self) -> Result<()> {
+        self.body.drain(..self.finger);
+        self.finger = 0usize;
+
+        while self.read_chunk()? {}
+
+        return Ok(());
+    }
+
+    // Streams the rest of the body to `writer`, returning the number of bytes written.
+    pub fn copy_to(&mut self, writer: &mut dyn io::Write) -> Result<u64> {
+        return Ok(io::copy(self, writer)?);
+    }
+
+    pub fn copy_to_file<P: AsRef<Path>>(&mut self, path: P) -> Result<u64> {
+        let mut file = fs::File::create(path)?;
+
+        return self.copy_to(&mut file);
+    }
+
+    // Receives the next piece of the body into `body`, false once it is complete.
+    fn read_chunk(&mut self) -> Result<bool> {
+        let source = match &mut self.source {
+            Some(source) => source,
+            None => return Ok(false),
+        };
+
+        let mut buffer = [0u8; LENGTH_READ];
+        let length = source.read(&mut buffer)?;
+
+        if length == 0usize {
+            // A content decoder may stop before the end of the body, which must still be read for the connection to be reused.
+            source.get_raw().drain()?;
+            self.source = None;
+
+            return Ok(false);
+        }
+
+        self.body.extend_from_slice(&buffer[..length]);
+
+        return Ok(true);
+    }
+
+    pub fn get_redirects(&self) -> &[Url] {
+        return &self.redirects;
+    }
+
+    pub(crate) fn set_redirects(&mut self, redirects: Vec<Url>) {
+        self.redirects = redirects;
+    }
+
+    pub fn is_interim(&self) -> bool {
+        return (100usize..200usize).contains(&self.status.get_code()) && self.status != Status::SwitchingProtocols;
+    }
+}
+
+// Reads the body, decoding its framing as it is received.
+impl io::Read for Response {
+    fn read(&mut self, output: &mut [u8]) -> io::Result<usize> {
+        if output.is_empty() {
+            return Ok(0usize);
+        }
+
+        while self.finger == self.body.len() {
+            if self.source.is_none() {
+                return Ok(0usize);
+            }
+
+            // Streamed data already read is not kept around.
+            self.body.clear();
+            self.finger = 0usize;
+
+            self.read_chunk().map_err(into_io_error)?;
+        }
+
+        let length = std::cmp::min(output.len(), self.body.len() - self.finger);
+
+        output[..length].copy_from_slice(&self.body[self.finger..self.finger + length]);
+        self.finger += length;
+
+        return Ok(length);
+    }
 }
 
 impl fmt::Display for Response {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        return formatter.write_fmt(format_args!("HTTP/1.1 {}\r\n\r\n", self.status));
+        let mut head = Vec::new();
+        codec::encode_response_head(&mut head, &self.status, &self.headers);
+
+        return formatter.write_str(&String::from_utf8_lossy(&head));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod assertions {
+        use crate::http::Method;
+        use crate::http::Status;
+        use crate::http::Limits;
+
+        use crate::http::codec::ParseMode;
+        use crate::http::codec::ResponseDecoder;
+
+        use super::super::Response;
+        use super::super::read_data;
+
+        // Reads the final response from `data` as an answer to `method`, body included.
+        fn parse(method: Method, data: &str) -> Response {
+            let mut reader = data.as_bytes();
+
+            let mut decoder = ResponseDecoder::new(ParseMode::Strict, &Limits::new());
+            decoder.set_method(method);
+
+            let mut response = Response::read_final_head(&mut decoder, &mut reader).ok().unwrap();
+
+            while read_data(&mut decoder, &mut reader, &mut response.body).ok().unwrap() {}
+
+            return response;
+        }
+
+        #[test]
+        fn parse_framing() {
+            let response = Response::parse(&mut "HTTP/1.1 201 Created\r\nContent-Length: 5\r\nX-Id: 7\r\n\r\nhello, ignored".as_bytes()).ok().unwrap();
+
+            assert_eq!(*response.get_status(), Status::Created);
+            assert_eq!(response.get_reason(), "Created");
+            assert_eq!(response.get_headers().get("X-Id"), Some("7"));
+            assert_eq!(response.get_content_length(), Some(5usize));
+            assert_eq!(response.get_body(), b"hello");
+
+            let response = Response::parse(&mut "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n7\r\n, world\r\n0\r\n\r\n".as_bytes()).ok().unwrap();
+
+            assert_eq!(response.get_body(), b"hello, world");
+
+            // Without framing the body runs until the connection is closed.
+            let response = Response::parse(&mut "HTTP/1.1 200 OK\r\nConnection: close\r\n\r\nuntil the end".as_bytes()).ok().unwrap();
+
+            assert_eq!(response.get_body(), b"until the end");
+
+            assert!(Response::parse(&mut "HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhel".as_bytes()).is_err());
+        }
+
+        #[test]
+        fn parse_without_body() {
+            assert!(parse(Method::HEAD, "HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\n").get_body().is_empty());
+            assert!(parse(Method::GET, "HTTP/1.1 204 No Content\r\n\r\n").get_body().is_empty());
+            assert!(parse(Method::GET, "HTTP/1.1 304 Not Modified\r\nContent-Length: 5\r\n\r\n").get_body().is_empty());
+        }
+
+        #[test]
+        fn parse_interim() {
+            let response = parse(Method::GET, "HTTP/1.1 100 Continue\r\n\r\nHTTP/1.1 103 Early Hints\r\nLink: </style.css>\r\n\r\nHTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok");
+
+            assert_eq!(*response.get_status(), Status::OK);
+            assert_eq!(response.get_headers().get("Link"), None);
+            assert_eq!(response.get_body(), b"ok");
+
+            // Switching protocols ends the exchange, it is not interim.
+            let response = parse(Method::GET, "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\n\r\n");
+
+            assert_eq!(*response.get_status(), Status::SwitchingProtocols);
+            assert!(!response.is_interim());
+        }
+
+        #[test]
+        fn keep_alive() {
+            let is_keep_alive = |data: &str| Response::parse(&mut data.as_bytes()).ok().unwrap().is_keep_alive();
+
+            assert!(is_keep_alive("HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n"));
+            assert!(!is_keep_alive("HTTP/1.1 200 OK\r\nConnection: Close\r\nContent-Length: 0\r\n\r\n"));
+            assert!(!is_keep_alive("HTTP/1.1 200 OK\r\nConnection: upgrade, close\r\nContent-Length: 0\r\n\r\n"));
+            assert!(!is_keep_alive("HTTP/1.0 200 OK\r\nContent-Length: 0\r\n\r\n"));
+            assert!(is_keep_alive("HTTP/1.0 200 OK\r\nConnection: keep-alive\r\nContent-Length: 0\r\n\r\n"));
+        }
     }
 }
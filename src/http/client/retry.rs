@@ -0,0 +1,216 @@
+use std::io;
+
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::hash::Hasher;
+
+use std::time::Duration;
+use std::time::SystemTime;
+
+use crate::http::Error;
+use crate::http::Status;
+
+use crate::http::date;
+
+const DEFAULT_MAX_RETRIES: u32 = 3u32;
+const DEFAULT_BASE_DELAY: Duration = Duration::from_millis(100u64);
+const DEFAULT_MAX_DELAY: Duration = Duration::from_secs(10u64);
+
+const DEFAULT_STATUSES: [Status; 4usize] = [Status::TooManyRequests, Status::BadGateway, Status::ServiceUnavailable, Status::GatewayTimeout];
+
+// When `Client` sends a request again after a connection error or one of `statuses`.
+// Only idempotent methods are retried unless `retry_non_idempotent` is set, and never requests with a streamed body.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    max_retries: u32,
+
+    // The delay before the n-th retry is `base_delay * 2^n`, up to `max_delay`, minus a random jitter of up to half.
+    base_delay: Duration,
+    max_delay: Duration,
+
+    statuses: Vec<Status>,
+
+    retry_non_idempotent: bool,
+}
+
+// Errors worth another try: the connection could not be made or broke down.
+fn is_transient(error: &Error) -> bool {
+    if let Error::InternalError(source) = error {
+        if let Some(error) = source.downcast_ref::<io::Error>() {
+            return matches!(error.kind(),
+                io::ErrorKind::ConnectionRefused | io::ErrorKind::ConnectionReset | io::ErrorKind::ConnectionAborted
+                | io::ErrorKind::BrokenPipe | io::ErrorKind::UnexpectedEof | io::ErrorKind::TimedOut);
+        }
+    }
+
+    return false;
+}
+
+// Random factor in [0.5, 1), from the randomly seeded hasher of the standard library.
+fn get_jitter() -> f64 {
+    let random = RandomState::new().build_hasher().finish();
+
+    return 0.5f64 + (random >> 11u32) as f64 / (1u64 << 54u32) as f64;
+}
+
+impl RetryPolicy {
+    pub fn new() -> Self {
+        return Self {
+            max_retries: DEFAULT_MAX_RETRIES,
+
+            base_delay: DEFAULT_BASE_DELAY,
+            max_delay: DEFAULT_MAX_DELAY,
+
+            statuses: DEFAULT_STATUSES.to_vec(),
+
+            retry_non_idempotent: false,
+        };
+    }
+
+    // Never retries, the default of `Client`.
+    pub fn none() -> Self {
+        let mut policy = Self::new();
+        policy.set_max_retries(0u32);
+
+        return policy;
+    }
+
+    pub fn set_max_retries(&mut self, max_retries: u32) {
+        self.max_retries = max_retries;
+    }
+
+    pub fn get_max_retries(&self) -> u32 {
+        return self.max_retries;
+    }
+
+    pub fn set_base_delay(&mut self, base_delay: Duration) {
+        self.base_delay = base_delay;
+    }
+
+    pub fn get_base_delay(&self) -> Duration {
+        return self.base_delay;
+    }
+
+    pub fn set_max_delay(&mut self, max_delay: Duration) {
+        self.max_delay = max_delay;
+    }
+
+    pub fn get_max_delay(&self) -> Duration {
+        return self.max_delay;
+    }
+
+    pub fn set_statuses(&mut self, statuses: Vec<Status>) {
+        self.statuses = statuses;
+    }
+
+    pub fn get_statuses(&self) -> &[Status] {
+        return &self.statuses;
+    }
+
+    pub fn set_retry_non_idempotent(&mut self, retry_non_idempotent: bool) {
+        self.retry_non_idempotent = retry_non_idempotent;
+    }
+
+    pub fn is_retrying_non_idempotent(&self) -> bool {
+        return self.retry_non_idempotent;
+    }
+
+    fn get_backoff(&self, attempt: u32) -> Duration {
+        let delay = self.base_delay.checked_mul(1u32 << attempt.min(16u32)).unwrap_or(self.max_delay).min(self.max_delay);
+
+        return delay.mul_f64(get_jitter());
+    }
+
+    // Delay before retrying after a failed attempt, None when it should not be retried.
+    pub(crate) fn get_delay_after_error(&self, attempt: u32, error: &Error) -> Option<Duration> {
+        if attempt >= self.max_retries || !is_transient(error) {
+            return None;
+        }
+
+        return Some(self.get_backoff(attempt));
+    }
+
+    // Same after a response, honoring its "Retry-After" (RFC 7231 7.1.3) unless it asks to wait longer than `max_delay`.
+    pub(crate) fn get_delay_after_status(&self, attempt: u32, status: &Status, retry_after: Option<&str>) -> Option<Duration> {
+        if attempt >= self.max_retries || !self.statuses.contains(status) {
+            return None;
+        }
+
+        let retry_after = match retry_after.map(str::trim) {
+            Some(retry_after) => match retry_after.parse::<u64>() {
+                Ok(seconds) => Some(Duration::from_secs(seconds)),
+                Err(_) => date::parse_http_date(retry_after)
+                    .map(|time| time.duration_since(SystemTime::now()).unwrap_or_default()),
+            },
+            None => None,
+        };
+
+        return match retry_after {
+            Some(retry_after) if retry_after > self.max_delay => None,
+            Some(retry_after) => Some(retry_after),
+            None => Some(self.get_backoff(attempt)),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod assertions {
+        use std::time::Duration;
+        use std::time::SystemTime;
+
+        use crate::http::Status;
+
+        use crate::http::date;
+
+        use super::super::RetryPolicy;
+        use super::super::get_jitter;
+
+        #[test]
+        fn backoff() {
+            for _ in 0usize..1000usize {
+                let jitter = get_jitter();
+
+                assert!((0.5f64..1f64).contains(&jitter));
+            }
+
+            let mut policy = RetryPolicy::new();
+            policy.set_max_retries(40u32);
+            policy.set_base_delay(Duration::from_millis(100u64));
+            policy.set_max_delay(Duration::from_secs(1u64));
+
+            for (attempt, delay) in [(0u32, 100u64), (1u32, 200u64), (3u32, 800u64), (4u32, 1000u64), (39u32, 1000u64)] {
+                let backoff = policy.get_backoff(attempt);
+
+                assert!(backoff >= Duration::from_millis(delay / 2u64) && backoff < Duration::from_millis(delay));
+            }
+
+            assert!(policy.get_delay_after_status(0u32, &Status::ServiceUnavailable, None).is_some());
+            assert_eq!(policy.get_delay_after_status(0u32, &Status::InternalServerError, None), None);
+            assert_eq!(policy.get_delay_after_status(40u32, &Status::ServiceUnavailable, None), None);
+        }
+
+        #[test]
+        fn retry_after() {
+            let mut policy = RetryPolicy::new();
+            policy.set_max_delay(Duration::from_secs(10u64));
+
+            assert_eq!(policy.get_delay_after_status(0u32, &Status::TooManyRequests, Some(" 2 ")), Some(Duration::from_secs(2u64)));
+            assert_eq!(policy.get_delay_after_status(0u32, &Status::TooManyRequests, Some("11")), None);
+
+            let later = date::format_http_date(SystemTime::now() + Duration::from_secs(3u64));
+            let delay = policy.get_delay_after_status(0u32, &Status::ServiceUnavailable, Some(&later)).unwrap();
+
+            assert!(delay > Duration::from_secs(1u64) && delay <= Duration::from_secs(3u64));
+
+            let later = date::format_http_date(SystemTime::now() + Duration::from_secs(60u64));
+
+            assert_eq!(policy.get_delay_after_status(0u32, &Status::ServiceUnavailable, Some(&later)), None);
+
+            // A date already past means no wait at all.
+            let earlier = date::format_http_date(SystemTime::now() - Duration::from_secs(60u64));
+
+            assert_eq!(policy.get_delay_after_status(0u32, &Status::ServiceUnavailable, Some(&earlier)), Some(Duration::ZERO));
+        }
+    }
+}
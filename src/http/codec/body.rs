@@ -0,0 +1,230 @@
+use std::ops::Range;
+
+use crate::http::Result;
+use crate::http::Error;
+
+use super::parse::Kind;
+
+const MAX_LENGTH_CHUNK_LINE: usize = 4096usize;
+
+// How the end of a message body is delimited (RFC 7230 3.3.3).
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Framing {
+    Length(usize),
+    Chunked,
+    // Only responses: the body ends when the connection is closed.
+    Close,
+}
+
+enum State {
+    Length(usize),
+    ChunkSize,
+    ChunkData(usize),
+    ChunkDataEnd,
+    Trailer,
+    Close,
+    Done,
+}
+
+pub(crate) struct BodyDecoder {
+    state: State,
+
+    kind: Kind,
+
+    max_length: usize,
+    total_length: usize,
+}
+
+// Returns the line at the start of `input` (excluding its CRLF) and the length consumed, if it is complete.
+fn take_line(input: &[u8], kind: Kind) -> Result<Option<(&[u8], usize)>> {
+    return match input.iter().position(|&byte| byte == b'\n') {
+        Some(index) => if index > 0usize && input[index - 1usize] == b'\r' {
+            Ok(Some((&input[..index - 1usize], index + 1usize)))
+        } else {
+            Err(kind.malformed())
+        },
+        None => if input.len() > MAX_LENGTH_CHUNK_LINE {
+            Err(kind.malformed())
+        } else {
+            Ok(None)
+        },
+    };
+}
+
+fn parse_chunk_size(line: &[u8], kind: Kind) -> Result<usize> {
+    let size = match line.iter().position(|&byte| byte == b';') {
+        Some(index) => &line[..index],
+        None => line,
+    };
+
+    let size = super::parse::trim_whitespace(size);
+
+    // `from_str_radix` alone would also accept a leading sign.
+    if size.is_empty() || !size.iter().all(|byte| byte.is_ascii_hexdigit()) {
+        return Err(kind.malformed());
+    }
+
+    return usize::from_str_radix(unsafe { std::str::from_utf8_unchecked(size) }, 16).map_err(|_| kind.malformed());
+}
+
+impl BodyDecoder {
+    pub(crate) fn new(framing: Framing, max_length: usize, kind: Kind) -> Self {
+        return Self {
+            state: match framing {
+                Framing::Length(0usize) => State::Done,
+                Framing::Length(length) => State::Length(length),
+                Framing::Chunked => State::ChunkSize,
+                Framing::Close => State::Close,
+            },
+
+            kind,
+
+            max_length,
+            total_length: 0usize,
+        };
+    }
+
+    pub(crate) fn is_done(&self) -> bool {
+        return matches!(self.state, State::Done);
+    }
+
+    fn take_data(&mut self, available: usize, remaining: usize) -> Result<usize> {
+        let length = std::cmp::min(available, remaining);
+
+        self.total_length += length;
+
+        if self.total_length > self.max_length {
+            return Err(match self.kind {
+                Kind::Request => Error::PayloadTooLarge,
+                Kind::Response => Error::BadResponse,
+            });
+        }
+
+        return Ok(length);
+    }
+
+    // Decodes the framing at the start of `input`: returns how many bytes were consumed, and where
+    // the body data is among them. Nothing is consumed while more input is needed to make progress.
+    pub(crate) fn decode(&mut self, input: &[u8]) -> Result<(usize, Range<usize>)> {
+        let mut consumed = 0usize;
+
+        loop {
+            let available = input.len() - consumed;
+
+            match self.state {
+                State::Done => return Ok((consumed, consumed..consumed)),
+                State::Length(remaining) | State::ChunkData(remaining) => {
+                    if available == 0usize {
+                        return Ok((consumed, consumed..consumed));
+                    }
+
+                    let length = self.take_data(available, remaining)?;
+
+                    self.state = match self.state {
+                        State::Length(_) if length == remaining => State::Done,
+                        State::Length(_) => State::Length(remaining - length),
+                        _ if length == remaining => State::ChunkDataEnd,
+                        _ => State::ChunkData(remaining - length),
+                    };
+
+                    return Ok((consumed + length, consumed..consumed + length));
+                }
+                State::Close => {
+                    if available == 0usize {
+                        return Ok((consumed, consumed..consumed));
+                    }
+
+                    let length = self.take_data(available, available)?;
+
+                    return Ok((consumed + length, consumed..consumed + length));
+                }
+                State::ChunkSize | State::ChunkDataEnd | State::Trailer => {
+                    let (line, length) = match take_line(&input[consumed..], self.kind)? {
+                        Some(line) => line,
+                        None => return Ok((consumed, consumed..consumed)),
+                    };
+
+                    self.state = match self.state {
+                        State::ChunkSize => match parse_chunk_size(line, self.kind)? {
+                            0usize => State::Trailer,
+                            size => State::ChunkData(size),
+                        },
+                        State::ChunkDataEnd => if line.is_empty() {
+                            State::ChunkSize
+                        } else {
+                            return Err(self.kind.malformed());
+                        },
+                        _ => if line.is_empty() { State::Done } else { State::Trailer },
+                    };
+
+                    consumed += length;
+                }
+            }
+        }
+    }
+
+    // The connection was closed: only a body delimited by the close is complete.
+    pub(crate) fn decode_eof(&mut self) -> Result<()> {
+        return match self.state {
+            State::Close | State::Done => {
+                self.state = State::Done;
+
+                Ok(())
+            }
+            _ => Err(self.kind.malformed()),
+        };
+    }
+}
+
+// Frames body data for sending, the counterpart of the decoders' body handling.
+pub struct BodyEncoder {
+    framing: Framing,
+}
+
+impl BodyEncoder {
+    pub fn new(framing: Framing) -> Self {
+        return Self {
+            framing,
+        };
+    }
+
+    pub fn encode(&mut self, data: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        if data.is_empty() {
+            return Ok(());
+        }
+
+        match self.framing {
+            Framing::Length(remaining) => {
+                if data.len() > remaining {
+                    return Err(Error::InvalidBody);
+                }
+
+                self.framing = Framing::Length(remaining - data.len());
+
+                output.extend_from_slice(data);
+            }
+            Framing::Chunked => {
+                output.extend_from_slice(format!("{:X}\r\n", data.len()).as_bytes());
+                output.extend_from_slice(data);
+                output.extend_from_slice(b"\r\n");
+            }
+            Framing::Close => {
+                output.extend_from_slice(data);
+            }
+        }
+
+        return Ok(());
+    }
+
+    pub fn finish(&mut self, output: &mut Vec<u8>) -> Result<()> {
+        return match self.framing {
+            Framing::Length(0usize) | Framing::Close => Ok(()),
+            Framing::Length(_) => Err(Error::InvalidBody),
+            Framing::Chunked => {
+                output.extend_from_slice(b"0\r\n\r\n");
+
+                Ok(())
+            }
+        };
+    }
+}
@@ -0,0 +1,441 @@
+use crate::http::Result;
+
+use crate::http::Method;
+use crate::http::Status;
+use crate::http::Limits;
+
+use super::parse::Kind;
+use super::body::BodyDecoder;
+use super::Framing;
+use super::ParseMode;
+use super::RequestRef;
+use super::ResponseRef;
+
+// What the decoders make of the bytes fed to them so far.
+pub enum Event<'a, H> {
+    Head(H),
+    Data(&'a [u8]),
+    End,
+}
+
+enum State {
+    Head,
+    Body(BodyDecoder),
+}
+
+// Buffering and body decoding shared by both decoders, which only differ in how they parse heads.
+struct Decoder {
+    buffer: Vec<u8>,
+    buffer_start: usize,
+    buffer_end: usize,
+
+    eof: bool,
+
+    state: State,
+
+    mode: ParseMode,
+    limits: Limits,
+}
+
+impl Decoder {
+    fn new(mode: ParseMode, limits: &Limits) -> Self {
+        return Self {
+            buffer: Vec::new(),
+            buffer_start: 0usize,
+            buffer_end: 0usize,
+
+            eof: false,
+
+            state: State::Head,
+
+            mode,
+            limits: *limits,
+        };
+    }
+
+    fn prepare(&mut self, length: usize) -> &mut [u8] {
+        if self.buffer_start == self.buffer_end {
+            self.buffer_start = 0usize;
+            self.buffer_end = 0usize;
+        } else if self.buffer_start != 0usize && self.buffer.len() - self.buffer_end < length {
+            self.buffer.copy_within(self.buffer_start..self.buffer_end, 0usize);
+            self.buffer_end -= self.buffer_start;
+            self.buffer_start = 0usize;
+        }
+
+        if self.buffer.len() - self.buffer_end < length {
+            self.buffer.resize(self.buffer_end + length, 0u8);
+        }
+
+        return &mut self.buffer[self.buffer_end..];
+    }
+
+    fn commit(&mut self, length: usize) {
+        self.buffer_end += length;
+    }
+
+    fn feed(&mut self, bytes: &[u8]) {
+        self.prepare(bytes.len())[..bytes.len()].copy_from_slice(bytes);
+        self.commit(bytes.len());
+    }
+
+    fn get_buffered(&self) -> &[u8] {
+        return &self.buffer[self.buffer_start..self.buffer_end];
+    }
+
+    fn poll_body<H>(&mut self) -> Result<Option<Event<'_, H>>> {
+        let decoder = match &mut self.state {
+            State::Body(decoder) => decoder,
+            State::Head => return Ok(None),
+        };
+
+        let (consumed, data) = decoder.decode(&self.buffer[self.buffer_start..self.buffer_end])?;
+
+        let data_start = self.buffer_start + data.start;
+        let data_end = self.buffer_start + data.end;
+
+        self.buffer_start += consumed;
+
+        if data_start != data_end {
+            return Ok(Some(Event::Data(&self.buffer[data_start..data_end])));
+        }
+
+        if !decoder.is_done() && self.eof {
+            decoder.decode_eof()?;
+        }
+
+        if decoder.is_done() {
+            self.state = State::Head;
+
+            return Ok(Some(Event::End));
+        }
+
+        return Ok(None);
+    }
+}
+
+// Sans-IO request decoder: bytes read from the connection are fed in, and the heads and body data
+// of the requests they carry come out as events. Requests may follow each other on the connection.
+pub struct RequestDecoder {
+    decoder: Decoder,
+}
+
+impl RequestDecoder {
+    pub fn new(mode: ParseMode, limits: &Limits) -> Self {
+        return Self {
+            decoder: Decoder::new(mode, limits),
+        };
+    }
+
+    pub fn feed(&mut self, bytes: &[u8]) {
+        self.decoder.feed(bytes);
+    }
+
+    // Returns space for at least `length` more bytes, to read into directly before calling `commit`.
+    pub fn prepare(&mut self, length: usize) -> &mut [u8] {
+        return self.decoder.prepare(length);
+    }
+
+    pub fn commit(&mut self, length: usize) {
+        self.decoder.commit(length);
+    }
+
+    // The connection was closed, no more bytes will be fed.
+    pub fn feed_eof(&mut self) {
+        self.decoder.eof = true;
+    }
+
+    // Bytes fed but not yet consumed by an event.
+    pub fn get_buffered(&self) -> &[u8] {
+        return self.decoder.get_buffered();
+    }
+
+    pub fn is_eof(&self) -> bool {
+        return self.decoder.eof;
+    }
+
+    // Returns the next event, or `None` when more bytes need to be fed first.
+    pub fn poll(&mut self) -> Result<Option<Event<'_, RequestRef<'_>>>> {
+        let decoder = &mut self.decoder;
+
+        if let State::Body(_) = decoder.state {
+            return decoder.poll_body();
+        }
+
+        let buffer = &decoder.buffer[decoder.buffer_start..decoder.buffer_end];
+
+        let head_length = match RequestRef::find_head_length(buffer, decoder.mode, &decoder.limits)? {
+            Some(head_length) => head_length,
+            None => {
+                if decoder.eof && !buffer.is_empty() {
+                    return Err(Kind::Request.malformed());
+                }
+
+                return Ok(None);
+            }
+        };
+
+        let head = RequestRef::parse_head(&buffer[..head_length], decoder.mode, &decoder.limits)?;
+
+        let framing = if head.is_chunked() {
+            Framing::Chunked
+        } else {
+            Framing::Length(head.get_content_length().unwrap_or(0usize))
+        };
+
+        decoder.buffer_start += head_length;
+        decoder.state = State::Body(BodyDecoder::new(framing, decoder.limits.get_max_length_body(), Kind::Request));
+
+        return Ok(Some(Event::Head(head)));
+    }
+}
+
+// Sans-IO response decoder, see `RequestDecoder`. Whether a response has a body depends on the
+// request it answers, so the decoder must be told the method of each request sent.
+pub struct ResponseDecoder {
+    decoder: Decoder,
+
+    method: Method,
+}
+
+impl ResponseDecoder {
+    pub fn new(mode: ParseMode, limits: &Limits) -> Self {
+        return Self {
+            decoder: Decoder::new(mode, limits),
+
+            method: Method::GET,
+        };
+    }
+
+    pub fn set_method(&mut self, method: Method) {
+        self.method = method;
+    }
+
+    pub fn feed(&mut self, bytes: &[u8]) {
+        self.decoder.feed(bytes);
+    }
+
+    // Returns space for at least `length` more bytes, to read into directly before calling `commit`.
+    pub fn prepare(&mut self, length: usize) -> &mut [u8] {
+        return self.decoder.prepare(length);
+    }
+
+    pub fn commit(&mut self, length: usize) {
+        self.decoder.commit(length);
+    }
+
+    // The connection was closed, no more bytes will be fed.
+    pub fn feed_eof(&mut self) {
+        self.decoder.eof = true;
+    }
+
+    // Bytes fed but not yet consumed by an event.
+    pub fn get_buffered(&self) -> &[u8] {
+        return self.decoder.get_buffered();
+    }
+
+    pub fn is_eof(&self) -> bool {
+        return self.decoder.eof;
+    }
+
+    // Returns the next event, or `None` when more bytes need to be fed first.
+    // Interim (1xx) responses come out as a head followed by an end, like any other.
+    pub fn poll(&mut self) -> Result<Option<Event<'_, ResponseRef<'_>>>> {
+        let decoder = &mut self.decoder;
+
+        if let State::Body(_) = decoder.state {
+            return decoder.poll_body();
+        }
+
+        let buffer = &decoder.buffer[decoder.buffer_start..decoder.buffer_end];
+
+        let head_length = match ResponseRef::find_head_length(buffer, decoder.mode, &decoder.limits)? {
+            Some(head_length) => head_length,
+            None => {
+                if decoder.eof && !buffer.is_empty() {
+                    return Err(Kind::Response.malformed());
+                }
+
+                return Ok(None);
+            }
+        };
+
+        let head = ResponseRef::parse_head(&buffer[..head_length], decoder.mode)?;
+
+        let code = head.get_status().get_code();
+
+        // RFC 7230 3.3.3
+        let bodyless = self.method == Method::HEAD
+            || (self.method == Method::CONNECT && (200usize..300usize).contains(&code))
+            || (100usize..200usize).contains(&code)
+            || *head.get_status() == Status::NoContent
+            || *head.get_status() == Status::NotModified;
+
+        let framing = if bodyless {
+            Framing::Length(0usize)
+        } else if head.is_chunked() {
+            Framing::Chunked
+        } else {
+            match head.get_content_length() {
+                Some(length) => Framing::Length(length),
+                None => Framing::Close,
+            }
+        };
+
+        decoder.buffer_start += head_length;
+        decoder.state = State::Body(BodyDecoder::new(framing, decoder.limits.get_max_length_body(), Kind::Response));
+
+        return Ok(Some(Event::Head(head)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::http::Headers;
+
+    use super::super::BodyEncoder;
+    use super::super::encode_request_head;
+    use super::super::encode_response_head;
+
+    // Target, header fields, and body of a decoded request.
+    type Message = (String, Vec<(String, String)>, Vec<u8>);
+
+    // Every message decoded, fed `step` bytes at a time.
+    fn decode_requests(bytes: &[u8], step: usize) -> Vec<Message> {
+        let mut decoder = RequestDecoder::new(ParseMode::Strict, &Limits::new());
+        let mut messages = Vec::new();
+
+        for chunk in bytes.chunks(step) {
+            decoder.feed(chunk);
+
+            while let Some(event) = decoder.poll().ok().unwrap() {
+                match event {
+                    Event::Head(head) => messages.push((
+                        String::from(head.get_target()),
+                        head.get_headers().iter().map(|&(name, value)| (String::from(name), String::from(value))).collect(),
+                        Vec::new(),
+                    )),
+                    Event::Data(data) => messages.last_mut().unwrap().2.extend_from_slice(data),
+                    Event::End => {}
+                }
+            }
+        }
+
+        return messages;
+    }
+
+    fn decode_responses(bytes: &[u8], step: usize, methods: &[Method]) -> Vec<(usize, Vec<u8>)> {
+        let mut decoder = ResponseDecoder::new(ParseMode::Strict, &Limits::new());
+        let mut messages = Vec::new();
+        let mut methods = methods.iter();
+
+        decoder.set_method(*methods.next().unwrap());
+
+        let mut chunks = bytes.chunks(step);
+
+        loop {
+            let chunk = chunks.next();
+
+            match chunk {
+                Some(chunk) => decoder.feed(chunk),
+                None => decoder.feed_eof(),
+            }
+
+            while let Some(event) = decoder.poll().ok().unwrap() {
+                match event {
+                    Event::Head(head) => messages.push((head.get_status().get_code(), Vec::new())),
+                    Event::Data(data) => messages.last_mut().unwrap().1.extend_from_slice(data),
+                    Event::End => if let Some(&method) = methods.next() {
+                        decoder.set_method(method);
+                    },
+                }
+            }
+
+            if chunk.is_none() {
+                return messages;
+            }
+        }
+    }
+
+    mod assertions {
+        #[test]
+        fn decode_request() {
+            use super::decode_requests;
+
+            const REQUESTS: &[u8] = b"POST /lorem HTTP/1.1\r\nContent-Length: 5\r\n\r\nipsum\
+                POST /dolor HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nsit\r\n5\r\n amet\r\n0\r\n\r\n\
+                GET / HTTP/1.1\r\nHost: localhost\r\n\r\n";
+
+            for &step in [1usize, 2usize, 7usize, REQUESTS.len()].iter() {
+                let requests = decode_requests(REQUESTS, step);
+
+                assert_eq!(3usize, requests.len());
+                assert_eq!(("/lorem", &b"ipsum"[..]), (requests[0usize].0.as_str(), requests[0usize].2.as_slice()));
+                assert_eq!(("/dolor", &b"sit amet"[..]), (requests[1usize].0.as_str(), requests[1usize].2.as_slice()));
+                assert_eq!(vec![(String::from("Host"), String::from("localhost"))], requests[2usize].1);
+                assert!(requests[2usize].2.is_empty());
+            }
+        }
+
+        #[test]
+        fn decode_response() {
+            use super::decode_responses;
+            use super::Method;
+
+            const RESPONSES: &[u8] = b"HTTP/1.1 100 Continue\r\n\r\n\
+                HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nlorem\
+                HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\n\
+                HTTP/1.1 204 No Content\r\n\r\n\
+                HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nipsum\r\n0\r\nExpires: 0\r\n\r\n\
+                HTTP/1.1 404 Not Found\r\n\r\ndolor sit amet";
+
+            let methods = [Method::POST, Method::POST, Method::HEAD, Method::DELETE, Method::GET, Method::GET];
+
+            for &step in [1usize, 3usize, RESPONSES.len()].iter() {
+                let responses = decode_responses(RESPONSES, step, &methods);
+
+                assert_eq!(vec![
+                    (100usize, Vec::new()),
+                    (200usize, b"lorem".to_vec()),
+                    (200usize, Vec::new()),
+                    (204usize, Vec::new()),
+                    (200usize, b"ipsum".to_vec()),
+                    (404usize, b"dolor sit amet".to_vec()),
+                ], responses);
+            }
+        }
+
+        #[test]
+        fn encode() {
+            use super::decode_requests;
+            use super::{encode_request_head, encode_response_head};
+            use super::{BodyEncoder, Framing};
+            use super::{Headers, Method, Status};
+
+            let mut headers = Headers::new();
+            headers.add("Host", "localhost");
+            headers.add("Transfer-Encoding", "chunked");
+
+            let mut output = Vec::new();
+            encode_request_head(&mut output, &Method::PUT, "/lorem", &headers);
+
+            let mut encoder = BodyEncoder::new(Framing::Chunked);
+            assert!(encoder.encode(b"ipsum", &mut output).is_ok());
+            assert!(encoder.encode(b" dolor", &mut output).is_ok());
+            assert!(encoder.finish(&mut output).is_ok());
+
+            assert_eq!(&b"PUT /lorem HTTP/1.1\r\nHost: localhost\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nipsum\r\n6\r\n dolor\r\n0\r\n\r\n"[..], output.as_slice());
+            assert_eq!(b"ipsum dolor".to_vec(), decode_requests(&output, 1usize)[0usize].2);
+
+            let mut output = Vec::new();
+            encode_response_head(&mut output, &Status::NotFound, &Headers::new());
+            assert_eq!(&b"HTTP/1.1 404 Not Found\r\n\r\n"[..], output.as_slice());
+
+            let mut encoder = BodyEncoder::new(Framing::Length(3usize));
+            assert!(encoder.encode(b"lorem", &mut output).is_err());
+            assert!(encoder.finish(&mut output).is_err());
+        }
+    }
+}
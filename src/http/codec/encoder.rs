@@ -0,0 +1,31 @@
+use crate::http::Method;
+use crate::http::Status;
+use crate::http::Headers;
+
+pub fn encode_request_head(output: &mut Vec<u8>, method: &Method, target: &str, headers: &Headers) {
+    output.extend_from_slice(method.get_text());
+    output.push(b' ');
+    output.extend_from_slice(target.as_bytes());
+    output.extend_from_slice(b" HTTP/1.1\r\n");
+
+    encode_headers(output, headers);
+}
+
+pub fn encode_response_head(output: &mut Vec<u8>, status: &Status, headers: &Headers) {
+    output.extend_from_slice(b"HTTP/1.1 ");
+    output.extend_from_slice(status.get_phrase());
+    output.extend_from_slice(b"\r\n");
+
+    encode_headers(output, headers);
+}
+
+fn encode_headers(output: &mut Vec<u8>, headers: &Headers) {
+    for (name, value) in headers.iter() {
+        output.extend_from_slice(name.as_bytes());
+        output.extend_from_slice(b": ");
+        output.extend_from_slice(value.as_bytes());
+        output.extend_from_slice(b"\r\n");
+    }
+
+    output.extend_from_slice(b"\r\n");
+}
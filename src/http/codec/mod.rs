@@ -0,0 +1,24 @@
+mod parse;
+
+mod request;
+mod response;
+
+mod body;
+
+mod decoder;
+mod encoder;
+
+pub use self::parse::ParseMode;
+
+pub use self::request::RequestRef;
+pub use self::response::ResponseRef;
+
+pub use self::body::Framing;
+pub use self::body::BodyEncoder;
+
+pub use self::decoder::Event;
+pub use self::decoder::RequestDecoder;
+pub use self::decoder::ResponseDecoder;
+
+pub use self::encoder::encode_request_head;
+pub use self::encoder::encode_response_head;
@@ -0,0 +1,327 @@
+use std::ops::Range;
+
+use crate::http::Result;
+use crate::http::Error;
+use crate::http::Limits;
+
+use crate::http::LENGTH_EOL;
+
+// How tolerant the parser is of message framing that RFC 7230 only allows for backward compatibility.
+// Anything that could make two parsers disagree on where a message ends (request smuggling) is
+// rejected by `Strict`; `Lenient` accepts bare LF line endings, obs-fold continuation lines, leading
+// empty lines, whitespace before the colon of a header field, and Transfer-Encoding alongside
+// Content-Length (in which case Content-Length is dropped, per RFC 7230 3.3.3).
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ParseMode {
+    Strict,
+    Lenient,
+}
+
+// Which side of the exchange is parsed, only used to pick the errors reported.
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub(crate) enum Kind {
+    Request,
+    Response,
+}
+
+impl Kind {
+    pub(crate) fn malformed(self) -> Error {
+        return match self {
+            Kind::Request => Error::BadRequest,
+            Kind::Response => Error::BadResponse,
+        };
+    }
+
+    fn start_line_too_long(self) -> Error {
+        return match self {
+            Kind::Request => Error::URITooLong,
+            Kind::Response => Error::BadResponse,
+        };
+    }
+
+    fn fields_too_large(self) -> Error {
+        return match self {
+            Kind::Request => Error::HeaderFieldsTooLarge,
+            Kind::Response => Error::BadResponse,
+        };
+    }
+}
+
+// RFC 7230 3.2.6: tchar = "!" / "#" / "$" / "%" / "&" / "'" / "*" / "+" / "-" / "." / "^" / "_" / "`" / "|" / "~" / DIGIT / ALPHA
+pub(crate) fn is_token(bytes: &[u8]) -> bool {
+    return !bytes.is_empty() && bytes.iter().all(|&byte| byte.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&byte));
+}
+
+// RFC 7230 3.2: field-vchar / SP / HTAB, including obs-text.
+pub(crate) fn is_field_value(bytes: &[u8]) -> bool {
+    return bytes.iter().all(|&byte| byte == b' ' || byte == b'\t' || (byte > 0x20u8 && byte != 0x7Fu8));
+}
+
+pub(crate) fn is_target(bytes: &[u8]) -> bool {
+    return bytes.iter().all(|&byte| byte > 0x20u8 && byte < 0x7Fu8);
+}
+
+pub(crate) fn trim_range(bytes: &[u8]) -> Range<usize> {
+    let mut range = 0usize..bytes.len();
+
+    while range.start < range.end && (bytes[range.start] == b' ' || bytes[range.start] == b'\t') {
+        range.start += 1usize;
+    }
+
+    while range.start < range.end && (bytes[range.end - 1usize] == b' ' || bytes[range.end - 1usize] == b'\t') {
+        range.end -= 1usize;
+    }
+
+    return range;
+}
+
+pub(crate) fn trim_whitespace(bytes: &[u8]) -> &[u8] {
+    return &bytes[trim_range(bytes)];
+}
+
+// Returns the length of the head (start line and header fields, up to and including the empty line) if it has been fully received.
+pub(crate) fn find_head_end(buffer: &[u8], mode: ParseMode, limits: &Limits, kind: Kind) -> Result<Option<usize>> {
+    let mut line_start = 0usize;
+    let mut line_count = 0usize;
+
+    let check_line_length = |line_length: usize, line_count: usize| -> Result<()> {
+        return if line_count == 0usize {
+            if line_length > limits.get_max_length_start_line() { Err(kind.start_line_too_long()) } else { Ok(()) }
+        } else if line_count > limits.get_max_count_header() || line_length > limits.get_max_length_header() {
+            Err(kind.fields_too_large())
+        } else {
+            Ok(())
+        };
+    };
+
+    for (index, &byte) in buffer.iter().enumerate() {
+        if byte != b'\n' {
+            continue;
+        }
+
+        let crlf = index > 0usize && buffer[index - 1usize] == b'\r';
+
+        if !crlf && mode == ParseMode::Strict {
+            return Err(kind.malformed());
+        }
+
+        let line_length = index - line_start - if crlf { 1usize } else { 0usize };
+
+        // Leading empty lines are tolerated in lenient mode, they do not end the head.
+        if line_length == 0usize {
+            if line_count != 0usize || mode == ParseMode::Strict {
+                return Ok(Some(index + 1usize));
+            }
+        } else {
+            check_line_length(line_length, line_count)?;
+
+            line_count += 1usize;
+        }
+
+        line_start = index + 1usize;
+    }
+
+    // The line still being received may already be too long.
+    check_line_length(buffer.len() - line_start, line_count)?;
+
+    if buffer.len() >= limits.get_max_length_head() {
+        return Err(if line_count == 0usize { kind.start_line_too_long() } else { kind.fields_too_large() });
+    }
+
+    return Ok(None);
+}
+
+// Returns the end of the line starting at `start` (excluding its terminator), and the start of the next one.
+pub(crate) fn find_line_end(buffer: &[u8], start: usize, mode: ParseMode, kind: Kind) -> Result<(usize, usize)> {
+    let lf_index = start + match buffer[start..].iter().position(|&byte| byte == b'\n') {
+        Some(index) => index,
+        None => return Err(kind.malformed()),
+    };
+
+    if lf_index > start && buffer[lf_index - 1usize] == b'\r' {
+        return Ok((lf_index - 1usize, lf_index + 1usize));
+    }
+
+    return match mode {
+        ParseMode::Strict => Err(kind.malformed()),
+        ParseMode::Lenient => Ok((lf_index, lf_index + 1usize)),
+    };
+}
+
+// Returns where the start line begins, past the empty lines the lenient parser lets through.
+pub(crate) fn skip_empty_lines(head: &[u8], mode: ParseMode) -> usize {
+    let mut finger = 0usize;
+
+    if mode == ParseMode::Lenient {
+        while head[finger..].starts_with(b"\r\n") || head[finger..].starts_with(b"\n") {
+            finger += if head[finger] == b'\r' { LENGTH_EOL } else { 1usize };
+        }
+    }
+
+    return finger;
+}
+
+// Parses the header fields of `head` from `start`, up to and including the empty line.
+// Values folded with obs-fold keep spanning the line breaks, they are only removed by `unfold`.
+pub(crate) fn parse_fields(head: &[u8], start: usize, mode: ParseMode, kind: Kind) -> Result<Vec<(&str, &str)>> {
+    let mut finger = start;
+
+    // Name, and start and end of the value in `head`.
+    let mut fields: Vec<(&str, usize, usize)> = Vec::new();
+
+    loop {
+        let (line_end, next_line_start) = find_line_end(head, finger, mode, kind)?;
+
+        let line = &head[finger..line_end];
+        let line_start = finger;
+
+        finger = next_line_start;
+
+        if line.is_empty() {
+            break;
+        }
+
+        if line[0] == b' ' || line[0] == b'\t' {
+            // obs-fold (RFC 7230 3.2.4): the line continues the value of the previous field.
+            let value = trim_range(line);
+
+            if mode == ParseMode::Strict || !is_field_value(&line[value.clone()]) {
+                return Err(kind.malformed());
+            }
+
+            match fields.last_mut() {
+                Some((_, value_start, value_end)) => {
+                    if *value_start == *value_end {
+                        *value_start = line_start + value.start;
+                    }
+
+                    *value_end = line_start + value.end;
+                }
+                None => return Err(kind.malformed()),
+            }
+
+            continue;
+        }
+
+        let colon_index = match line.iter().position(|&byte| byte == b':') {
+            Some(index) => index,
+            None => return Err(kind.malformed()),
+        };
+
+        let mut name = &line[..colon_index];
+
+        if name.ends_with(b" ") || name.ends_with(b"\t") {
+            if mode == ParseMode::Strict {
+                return Err(kind.malformed());
+            }
+
+            name = trim_whitespace(name);
+        }
+
+        let value = trim_range(&line[colon_index + 1usize..]);
+        let value_start = line_start + colon_index + 1usize + value.start;
+        let value_end = line_start + colon_index + 1usize + value.end;
+
+        if !is_token(name) || !is_field_value(&head[value_start..value_end]) {
+            return Err(kind.malformed());
+        }
+
+        // Tokens are ASCII.
+        fields.push((unsafe { std::str::from_utf8_unchecked(name) }, value_start, value_end));
+    }
+
+    let mut headers = Vec::with_capacity(fields.len());
+
+    for (name, value_start, value_end) in fields.into_iter() {
+        // The field value may only carry UTF-8 as obs-text.
+        let value = std::str::from_utf8(&head[value_start..value_end]).map_err(|_| kind.malformed())?;
+
+        headers.push((name, value));
+    }
+
+    return Ok(headers);
+}
+
+fn parse_content_length(value: &str, kind: Kind) -> Result<usize> {
+    let mut content_length = None;
+
+    // A list of identical values ("5, 5") may come from a proxy merging duplicated fields (RFC 7230 3.3.2).
+    for value in value.split(',') {
+        let value = value.trim_matches(&[' ', '\t'][..]);
+
+        if value.is_empty() || !value.bytes().all(|byte| byte.is_ascii_digit()) {
+            return Err(kind.malformed());
+        }
+
+        let length = match value.parse::<usize>() {
+            Ok(length) => length,
+            Err(_) => return Err(kind.malformed()),
+        };
+
+        if content_length.is_some_and(|content_length| content_length != length) {
+            return Err(kind.malformed());
+        }
+
+        content_length = Some(length);
+    }
+
+    return match content_length {
+        Some(length) => Ok(length),
+        None => Err(kind.malformed()),
+    };
+}
+
+// Reads the message framing from the Transfer-Encoding and Content-Length fields: the content length, and whether the body is chunked.
+// In lenient mode, Content-Length is removed from `headers` when both are present.
+pub(crate) fn parse_framing(headers: &mut Vec<(&str, &str)>, mode: ParseMode, kind: Kind) -> Result<(Option<usize>, bool)> {
+    let mut chunked = false;
+
+    for (_, value) in headers.iter().filter(|(name, _)| name.eq_ignore_ascii_case("Transfer-Encoding")) {
+        for coding in value.split(',') {
+            // Chunked is the only supported coding, and it must be applied exactly once.
+            if chunked || !coding.trim_matches(&[' ', '\t'][..]).eq_ignore_ascii_case("chunked") {
+                return Err(kind.malformed());
+            }
+
+            chunked = true;
+        }
+    }
+
+    let mut content_length = None;
+
+    for (_, value) in headers.iter().filter(|(name, _)| name.eq_ignore_ascii_case("Content-Length")) {
+        let length = parse_content_length(value, kind)?;
+
+        if content_length.is_some_and(|content_length| content_length != length) {
+            return Err(kind.malformed());
+        }
+
+        content_length = Some(length);
+    }
+
+    if chunked && content_length.is_some() {
+        if mode == ParseMode::Strict {
+            return Err(kind.malformed());
+        }
+
+        headers.retain(|(name, _)| !name.eq_ignore_ascii_case("Content-Length"));
+        content_length = None;
+    }
+
+    return Ok((content_length, chunked));
+}
+
+// Joins the lines of a value folded with obs-fold, which only the lenient parser lets through.
+pub(crate) fn unfold(value: &str) -> String {
+    let mut unfolded = String::with_capacity(value.len());
+
+    for (index, line) in value.split('\n').enumerate() {
+        if index != 0usize {
+            unfolded.push(' ');
+        }
+
+        unfolded.push_str(line.trim_matches(&[' ', '\t', '\r'][..]));
+    }
+
+    return unfolded;
+}
@@ -0,0 +1,199 @@
+use crate::http::Result;
+use crate::http::Error;
+
+use crate::http::{MIN_LENGTH_METHOD, LENGTH_SPACE, MIN_LENGTH_TARGET, LENGTH_PROTOCOL};
+
+use crate::http::Method;
+use crate::http::Headers;
+use crate::http::Limits;
+
+use super::parse;
+use super::parse::Kind;
+use super::ParseMode;
+
+const MIN_LENGTH_REQUEST_LINE: usize = MIN_LENGTH_METHOD + LENGTH_SPACE + MIN_LENGTH_TARGET + LENGTH_SPACE + LENGTH_PROTOCOL;
+// "GET / HTTP/1.1"
+
+// Request head borrowed from the buffer it was parsed from: the method, target, and header fields
+// are not copied, which avoids allocating for requests that are only inspected or forwarded.
+pub struct RequestRef<'a> {
+    method: Method,
+
+    target: &'a str,
+    // Whether a trailing slash was removed from the target, which only matters to handlers serving directories.
+    trailing_slash: bool,
+
+    headers: Vec<(&'a str, &'a str)>,
+
+    content_length: Option<usize>,
+    chunked: bool,
+
+    head_length: usize,
+}
+
+impl<'a> RequestRef<'a> {
+    // Returns `None` while the head has not been fully received.
+    pub fn parse(buffer: &'a [u8], mode: ParseMode, limits: &Limits) -> Result<Option<Self>> {
+        return match Self::find_head_length(buffer, mode, limits)? {
+            Some(head_length) => Ok(Some(Self::parse_head(&buffer[..head_length], mode, limits)?)),
+            None => Ok(None),
+        };
+    }
+
+    // Returns the length of the head if it has been fully received, without parsing it.
+    pub fn find_head_length(buffer: &[u8], mode: ParseMode, limits: &Limits) -> Result<Option<usize>> {
+        return parse::find_head_end(buffer, mode, limits, Kind::Request);
+    }
+
+    // `head` must end right after the empty line terminating the head.
+    pub fn parse_head(head: &'a [u8], mode: ParseMode, limits: &Limits) -> Result<Self> {
+        let mut finger = parse::skip_empty_lines(head, mode);
+
+        let (line_end, next_line_start) = parse::find_line_end(head, finger, mode, Kind::Request)?;
+
+        if finger + MIN_LENGTH_REQUEST_LINE > line_end {
+            return Err(Error::BadRequest);
+        }
+
+        let method = {
+            let space_index = finger + match head[finger..line_end].iter().position(|&byte| byte == b' ') {
+                Some(index) => index,
+                None => return Err(Error::BadRequest),
+            };
+
+            let method = match Method::from_text(&head[finger..space_index]) {
+                Some(method) => method,
+                None => return Err(Error::BadRequest),
+            };
+
+            finger = space_index + LENGTH_SPACE;
+
+            method
+        };
+
+        let (target, trailing_slash) = {
+            let space_index = finger + match head[finger..line_end].iter().position(|&byte| byte == b' ') {
+                Some(index) => index,
+                None => return Err(Error::BadRequest),
+            };
+
+            if !parse::is_target(&head[finger..space_index]) {
+                return Err(Error::BadRequest);
+            }
+
+            let target = if head[finger] == b'/' {
+                if finger + 1usize == space_index {
+                    "/"
+                } else if head[space_index - 1usize] == b'/' {
+                    std::str::from_utf8(&head[finger..space_index - 1usize])?
+                } else {
+                    std::str::from_utf8(&head[finger..space_index])?
+                }
+            } else {
+                return Err(Error::BadRequest);
+            };
+
+            let trailing_slash = head[space_index - 1usize] == b'/';
+
+            finger = space_index + LENGTH_SPACE;
+
+            (target, trailing_slash)
+        };
+
+        let version = &head[finger..line_end];
+
+        if version != b"HTTP/1.1" {
+            // A well-formed version of another protocol revision is answered with 505, anything else is malformed.
+            if let [b'H', b'T', b'T', b'P', b'/', major, b'.', minor] = version {
+                if major.is_ascii_digit() && minor.is_ascii_digit() {
+                    return Err(Error::UnsupportedProtocolVersion);
+                }
+            }
+
+            return Err(Error::BadRequest);
+        }
+
+        let mut headers = parse::parse_fields(head, next_line_start, mode, Kind::Request)?;
+
+        if headers.iter().filter(|(name, _)| name.eq_ignore_ascii_case("Host")).count() > 1usize {
+            return Err(Error::BadRequest);
+        }
+
+        let (content_length, chunked) = parse::parse_framing(&mut headers, mode, Kind::Request)?;
+
+        if content_length.is_some_and(|length| length > limits.get_max_length_body()) {
+            return Err(Error::PayloadTooLarge);
+        }
+
+        return Ok(Self {
+            method,
+
+            target,
+            trailing_slash,
+
+            headers,
+
+            content_length,
+            chunked,
+
+            head_length: head.len(),
+        });
+    }
+
+    pub fn get_method(&self) -> &Method {
+        return &self.method;
+    }
+
+    pub fn get_target(&self) -> &'a str {
+        return self.target;
+    }
+
+    // Whether the target ended with a slash, which `get_target` leaves out.
+    pub fn has_trailing_slash(&self) -> bool {
+        return self.trailing_slash;
+    }
+
+    pub fn get_header(&self, name: &str) -> Option<&'a str> {
+        return self.headers.iter()
+            .find(|(header_name, _)| header_name.eq_ignore_ascii_case(name))
+            .map(|&(_, value)| value);
+    }
+
+    pub fn get_all_headers<'b>(&'b self, name: &'b str) -> impl Iterator<Item=&'a str> + 'b {
+        return self.headers.iter()
+            .filter(move |(header_name, _)| header_name.eq_ignore_ascii_case(name))
+            .map(|&(_, value)| value);
+    }
+
+    pub fn get_headers(&self) -> &[(&'a str, &'a str)] {
+        return &self.headers;
+    }
+
+    pub fn get_content_length(&self) -> Option<usize> {
+        return self.content_length;
+    }
+
+    pub fn is_chunked(&self) -> bool {
+        return self.chunked;
+    }
+
+    // Length of the head in the buffer, the body starts right after it.
+    pub fn get_head_length(&self) -> usize {
+        return self.head_length;
+    }
+
+    // Copies the header fields, joining values folded over several lines.
+    pub fn to_headers(&self) -> Headers {
+        let mut headers = Headers::new();
+
+        for &(name, value) in self.headers.iter() {
+            if value.contains('\n') {
+                headers.add(name, &parse::unfold(value));
+            } else {
+                headers.add(name, value);
+            }
+        }
+
+        return headers;
+    }
+}
@@ -0,0 +1,161 @@
+use crate::http::Result;
+use crate::http::Error;
+
+use crate::http::{LENGTH_SPACE, LENGTH_PROTOCOL};
+
+use crate::http::Status;
+use crate::http::Headers;
+use crate::http::Limits;
+
+use super::parse;
+use super::parse::Kind;
+use super::ParseMode;
+
+const LENGTH_STATUS_CODE: usize = 3usize; // "200"
+
+const MIN_LENGTH_STATUS_LINE: usize = LENGTH_PROTOCOL + LENGTH_SPACE + LENGTH_STATUS_CODE;
+// "HTTP/1.1 200"
+
+// Response head borrowed from the buffer it was parsed from, see `RequestRef`.
+pub struct ResponseRef<'a> {
+    version: &'a str,
+
+    status: Status,
+
+    reason: &'a str,
+
+    headers: Vec<(&'a str, &'a str)>,
+
+    content_length: Option<usize>,
+    chunked: bool,
+
+    head_length: usize,
+}
+
+impl<'a> ResponseRef<'a> {
+    // Returns `None` while the head has not been fully received.
+    pub fn parse(buffer: &'a [u8], mode: ParseMode, limits: &Limits) -> Result<Option<Self>> {
+        return match Self::find_head_length(buffer, mode, limits)? {
+            Some(head_length) => Ok(Some(Self::parse_head(&buffer[..head_length], mode)?)),
+            None => Ok(None),
+        };
+    }
+
+    // Returns the length of the head if it has been fully received, without parsing it.
+    pub fn find_head_length(buffer: &[u8], mode: ParseMode, limits: &Limits) -> Result<Option<usize>> {
+        return parse::find_head_end(buffer, mode, limits, Kind::Response);
+    }
+
+    // `head` must end right after the empty line terminating the head.
+    pub fn parse_head(head: &'a [u8], mode: ParseMode) -> Result<Self> {
+        let mut finger = parse::skip_empty_lines(head, mode);
+
+        let (line_end, next_line_start) = parse::find_line_end(head, finger, mode, Kind::Response)?;
+
+        if finger + MIN_LENGTH_STATUS_LINE > line_end {
+            return Err(Error::BadResponse);
+        }
+
+        // HTTP/1.0 servers are still around, their responses parse the same.
+        let version = if head[finger..].starts_with(b"HTTP/1.1 ") {
+            "HTTP/1.1"
+        } else if head[finger..].starts_with(b"HTTP/1.0 ") {
+            "HTTP/1.0"
+        } else {
+            return Err(Error::BadResponse);
+        };
+
+        finger += LENGTH_PROTOCOL + LENGTH_SPACE;
+
+        let status = match Status::from_code_text(&head[finger..finger + LENGTH_STATUS_CODE]) {
+            Some(status) => status,
+            None => return Err(Error::BadResponse),
+        };
+
+        finger += LENGTH_STATUS_CODE;
+
+        // The reason phrase may be empty, some servers even leave out the space before it.
+        let reason = if finger == line_end {
+            ""
+        } else if head[finger] == b' ' && parse::is_field_value(&head[finger + LENGTH_SPACE..line_end]) {
+            std::str::from_utf8(&head[finger + LENGTH_SPACE..line_end]).map_err(|_| Error::BadResponse)?
+        } else {
+            return Err(Error::BadResponse);
+        };
+
+        let mut headers = parse::parse_fields(head, next_line_start, mode, Kind::Response)?;
+
+        let (content_length, chunked) = parse::parse_framing(&mut headers, mode, Kind::Response)?;
+
+        return Ok(Self {
+            version,
+
+            status,
+
+            reason,
+
+            headers,
+
+            content_length,
+            chunked,
+
+            head_length: head.len(),
+        });
+    }
+
+    pub fn get_version(&self) -> &'a str {
+        return self.version;
+    }
+
+    pub fn get_status(&self) -> &Status {
+        return &self.status;
+    }
+
+    pub fn get_reason(&self) -> &'a str {
+        return self.reason;
+    }
+
+    pub fn get_header(&self, name: &str) -> Option<&'a str> {
+        return self.headers.iter()
+            .find(|(header_name, _)| header_name.eq_ignore_ascii_case(name))
+            .map(|&(_, value)| value);
+    }
+
+    pub fn get_all_headers<'b>(&'b self, name: &'b str) -> impl Iterator<Item=&'a str> + 'b {
+        return self.headers.iter()
+            .filter(move |(header_name, _)| header_name.eq_ignore_ascii_case(name))
+            .map(|&(_, value)| value);
+    }
+
+    pub fn get_headers(&self) -> &[(&'a str, &'a str)] {
+        return &self.headers;
+    }
+
+    pub fn get_content_length(&self) -> Option<usize> {
+        return self.content_length;
+    }
+
+    pub fn is_chunked(&self) -> bool {
+        return self.chunked;
+    }
+
+    // Length of the head in the buffer, the body starts right after it.
+    pub fn get_head_length(&self) -> usize {
+        return self.head_length;
+    }
+
+    // Copies the header fields, joining values folded over several lines.
+    pub fn to_headers(&self) -> Headers {
+        let mut headers = Headers::new();
+
+        for &(name, value) in self.headers.iter() {
+            if value.contains('\n') {
+                headers.add(name, &parse::unfold(value));
+            } else {
+                headers.add(name, value);
+            }
+        }
+
+        return headers;
+    }
+}
@@ -0,0 +1,155 @@
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+const MONTHS: [&str; 12usize] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+const WEEKDAYS: [&str; 7usize] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+
+const SECONDS_PER_DAY: u64 = 86400u64;
+
+// Days since 1970-01-01 of a proleptic Gregorian date.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2u32 { year - 1i64 } else { year };
+    let era = year.div_euclid(400i64);
+    let year_of_era = year - era * 400i64;
+    let day_of_year = (153i64 * ((month as i64 + 9i64) % 12i64) + 2i64) / 5i64 + day as i64 - 1i64;
+    let day_of_era = year_of_era * 365i64 + year_of_era / 4i64 - year_of_era / 100i64 + day_of_year;
+
+    return era * 146097i64 + day_of_era - 719468i64;
+}
+
+// Proleptic Gregorian date of a number of days since 1970-01-01, as (year, month, day).
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468i64;
+    let era = days.div_euclid(146097i64);
+    let day_of_era = days - era * 146097i64;
+    let year_of_era = (day_of_era - day_of_era / 1460i64 + day_of_era / 36524i64 - day_of_era / 146096i64) / 365i64;
+    let day_of_year = day_of_era - (365i64 * year_of_era + year_of_era / 4i64 - year_of_era / 100i64);
+    let month_index = (5i64 * day_of_year + 2i64) / 153i64;
+    let day = (day_of_year - (153i64 * month_index + 2i64) / 5i64 + 1i64) as u32;
+    let month = if month_index < 10i64 { month_index + 3i64 } else { month_index - 9i64 } as u32;
+
+    return (year_of_era + era * 400i64 + if month <= 2u32 { 1i64 } else { 0i64 }, month, day);
+}
+
+// Date and time of day in seconds, in UTC.
+fn split_time(time: SystemTime) -> ((i64, u32, u32), u64, u32) {
+    let elapsed = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let seconds = elapsed.as_secs();
+
+    return (civil_from_days((seconds / SECONDS_PER_DAY) as i64), seconds % SECONDS_PER_DAY, elapsed.subsec_millis());
+}
+
+fn parse_month(month: &str) -> Option<u32> {
+    return MONTHS.iter().position(|name| *name == month).map(|index| index as u32 + 1u32);
+}
+
+fn parse_time(time: &str) -> Option<u64> {
+    let mut parts = time.split(':');
+
+    let hours = parts.next()?.parse::<u64>().ok()?;
+    let minutes = parts.next()?.parse::<u64>().ok()?;
+    let seconds = parts.next()?.parse::<u64>().ok()?;
+
+    if parts.next().is_some() || hours > 23u64 || minutes > 59u64 || seconds > 60u64 {
+        return None;
+    }
+
+    return Some(hours * 3600u64 + minutes * 60u64 + seconds);
+}
+
+// Accepts the three formats HTTP recipients must understand (RFC 7231 7.1.1.1):
+// IMF-fixdate, the obsolete RFC 850 format, and ANSI C's asctime() format.
+pub(crate) fn parse_http_date(text: &str) -> Option<SystemTime> {
+    let tokens: Vec<&str> = text.split(&[' ', ','][..]).filter(|token| !token.is_empty()).collect();
+
+    let (year, month, day, time) = match tokens.as_slice() {
+        // "Sun, 06 Nov 1994 08:49:37 GMT"
+        [_, day, month, year, time, "GMT"] => (year.parse::<i64>().ok()?, parse_month(month)?, day.parse::<u32>().ok()?, *time),
+        // "Sunday, 06-Nov-94 08:49:37 GMT"
+        [_, date, time, "GMT"] => {
+            let mut parts = date.split('-');
+
+            let day = parts.next()?.parse::<u32>().ok()?;
+            let month = parse_month(parts.next()?)?;
+            let year = parts.next()?.parse::<i64>().ok()?;
+
+            (if year < 70i64 { 2000i64 + year } else if year < 100i64 { 1900i64 + year } else { year }, month, day, *time)
+        }
+        // "Sun Nov  6 08:49:37 1994"
+        [_, month, day, time, year] => (year.parse::<i64>().ok()?, parse_month(month)?, day.parse::<u32>().ok()?, *time),
+        _ => return None,
+    };
+
+    if year < 1970i64 || day == 0u32 || day > 31u32 {
+        return None;
+    }
+
+    let seconds = days_from_civil(year, month, day) as u64 * SECONDS_PER_DAY + parse_time(time)?;
+
+    return Some(UNIX_EPOCH + Duration::from_secs(seconds));
+}
+
+// Time as in the IMF-fixdate format HTTP senders must use, "Sun, 06 Nov 1994 08:49:37 GMT".
+pub(crate) fn format_http_date(time: SystemTime) -> String {
+    let days = time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() / SECONDS_PER_DAY;
+    let ((year, month, day), seconds, _) = split_time(time);
+
+    // 1970-01-01 was a Thursday.
+    return format!("{}, {:02} {} {:04} {:02}:{:02}:{:02} GMT",
+        WEEKDAYS[(days % 7u64) as usize], day, MONTHS[month as usize - 1usize], year, seconds / 3600u64, seconds / 60u64 % 60u64, seconds % 60u64);
+}
+
+// Time as in the Common Log Format, "10/Oct/2000:13:55:36 +0000", always in UTC.
+pub(crate) fn format_log_date(time: SystemTime) -> String {
+    let ((year, month, day), seconds, _) = split_time(time);
+
+    return format!("{:02}/{}/{:04}:{:02}:{:02}:{:02} +0000",
+        day, MONTHS[month as usize - 1usize], year, seconds / 3600u64, seconds / 60u64 % 60u64, seconds % 60u64);
+}
+
+// Time as in RFC 3339, "2000-10-10T13:55:36.000Z", with milliseconds.
+pub(crate) fn format_rfc3339_date(time: SystemTime) -> String {
+    let ((year, month, day), seconds, milliseconds) = split_time(time);
+
+    return format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year, month, day, seconds / 3600u64, seconds / 60u64 % 60u64, seconds % 60u64, milliseconds);
+}
+
+#[cfg(test)]
+mod tests {
+    mod assertions {
+        use std::time::Duration;
+        use std::time::UNIX_EPOCH;
+
+        use super::super::parse_http_date;
+        use super::super::format_http_date;
+        use super::super::format_log_date;
+        use super::super::format_rfc3339_date;
+
+        #[test]
+        fn parse() {
+            let expected = Some(UNIX_EPOCH + Duration::from_secs(784111777u64));
+
+            assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"), expected);
+            assert_eq!(parse_http_date("Sunday, 06-Nov-94 08:49:37 GMT"), expected);
+            assert_eq!(parse_http_date("Sun Nov  6 08:49:37 1994"), expected);
+
+            assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37 UTC"), None);
+            assert_eq!(parse_http_date("Sun, 06 Foo 1994 08:49:37 GMT"), None);
+            assert_eq!(parse_http_date("Sun, 06 Nov 1994 24:49:37 GMT"), None);
+            assert_eq!(parse_http_date("120"), None);
+        }
+
+        #[test]
+        fn format() {
+            let time = UNIX_EPOCH + Duration::from_millis(971186136042u64);
+
+            assert_eq!(format_log_date(time), "10/Oct/2000:13:55:36 +0000");
+            assert_eq!(format_rfc3339_date(time), "2000-10-10T13:55:36.042Z");
+            assert_eq!(format_http_date(UNIX_EPOCH + Duration::from_secs(784111777u64)), "Sun, 06 Nov 1994 08:49:37 GMT");
+            assert_eq!(format_log_date(UNIX_EPOCH + Duration::from_secs(951782400u64)), "29/Feb/2000:00:00:00 +0000");
+        }
+    }
+}
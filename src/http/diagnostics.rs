@@ -0,0 +1,73 @@
+#[cfg(not(any(feature = "log", feature = "tracing")))]
+use std::io;
+#[cfg(not(any(feature = "log", feature = "tracing")))]
+use std::fmt;
+
+// Diagnostics go through "tracing" or "log" when one of these features is enabled.
+// Without them, errors and warnings are still written to stderr, and everything else is dropped.
+macro_rules! emit {
+    ($level:ident, $($argument:tt)+) => {{
+        #[cfg(feature = "tracing")]
+        tracing::$level!($($argument)+);
+
+        #[cfg(all(feature = "log", not(feature = "tracing")))]
+        log::$level!($($argument)+);
+
+        #[cfg(not(any(feature = "log", feature = "tracing")))]
+        $crate::http::diagnostics::fallback(stringify!($level), format_args!($($argument)+));
+    }};
+}
+
+macro_rules! error {
+    ($($argument:tt)+) => {
+        $crate::http::diagnostics::emit!(error, $($argument)+)
+    };
+}
+
+macro_rules! warning {
+    ($($argument:tt)+) => {
+        $crate::http::diagnostics::emit!(warn, $($argument)+)
+    };
+}
+
+macro_rules! debug {
+    ($($argument:tt)+) => {
+        $crate::http::diagnostics::emit!(debug, $($argument)+)
+    };
+}
+
+pub(crate) use emit;
+pub(crate) use error;
+pub(crate) use warning;
+pub(crate) use debug;
+
+#[cfg(not(any(feature = "log", feature = "tracing")))]
+pub(crate) fn fallback(level: &str, arguments: fmt::Arguments) {
+    write_fallback(&mut io::stderr(), level, arguments);
+}
+
+#[cfg(not(any(feature = "log", feature = "tracing")))]
+fn write_fallback(writer: &mut dyn io::Write, level: &str, arguments: fmt::Arguments) {
+    if level == "error" || level == "warn" {
+        // Failing to report is not worth failing for.
+        let _ = writeln!(writer, "{}: {}", level, arguments);
+    }
+}
+
+#[cfg(all(test, not(any(feature = "log", feature = "tracing"))))]
+mod tests {
+    mod assertions {
+        use super::super::write_fallback;
+
+        #[test]
+        fn fallback() {
+            let mut output = Vec::new();
+
+            write_fallback(&mut output, "error", format_args!("accept failed: {}", "reset"));
+            write_fallback(&mut output, "warn", format_args!("slow request"));
+            write_fallback(&mut output, "debug", format_args!("retrying"));
+
+            assert_eq!(output, b"error: accept failed: reset\nwarn: slow request\n");
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use std::fmt;
+
+// Header fields of a request or response, in the order they were added.
+// Field names are compared case-insensitively (RFC 7230 3.2).
+pub struct Headers {
+    entries: Vec<(String, String)>,
+}
+
+impl Headers {
+    pub fn new() -> Self {
+        return Self {
+            entries: Vec::new(),
+        };
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        return self.entries.iter()
+            .find(|(entry_name, _)| entry_name.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str());
+    }
+
+    pub fn get_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item=&'a str> + 'a {
+        return self.entries.iter()
+            .filter(move |(entry_name, _)| entry_name.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str());
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        return self.entries.iter().any(|(entry_name, _)| entry_name.eq_ignore_ascii_case(name));
+    }
+
+    pub fn add(&mut self, name: &str, value: &str) {
+        self.entries.push((String::from(name), String::from(value)));
+    }
+
+    pub fn set(&mut self, name: &str, value: &str) {
+        self.remove(name);
+        self.add(name, value);
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.entries.retain(|(entry_name, _)| !entry_name.eq_ignore_ascii_case(name));
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item=(&str, &str)> {
+        return self.entries.iter().map(|(name, value)| (name.as_str(), value.as_str()));
+    }
+
+    pub fn len(&self) -> usize {
+        return self.entries.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.entries.is_empty();
+    }
+}
+
+impl fmt::Display for Headers {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        for (name, value) in self.entries.iter() {
+            formatter.write_fmt(format_args!("{}: {}\r\n", name, value))?;
+        }
+
+        return Ok(());
+    }
+}
@@ -0,0 +1,22 @@
+// String as a JSON string literal, quotes included (RFC 8259 7).
+pub(crate) fn escape(value: &str) -> String {
+    let mut output = String::with_capacity(value.len() + 2usize);
+
+    output.push('"');
+
+    for character in value.chars() {
+        match character {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            character if character.is_control() => output.push_str(&format!("\\u{:04x}", character as u32)),
+            character => output.push(character),
+        }
+    }
+
+    output.push('"');
+
+    return output;
+}
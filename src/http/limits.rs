@@ -0,0 +1,70 @@
+// Bounds on the size of the messages the parsers accept.
+#[derive(Clone, Copy, Debug)]
+pub struct Limits {
+    max_length_start_line: usize,
+    max_length_header: usize,
+    max_count_header: usize,
+    max_length_head: usize,
+    max_length_body: usize,
+}
+
+const DEFAULT_MAX_LENGTH_START_LINE: usize = 8usize * 1024usize;
+const DEFAULT_MAX_LENGTH_HEADER: usize = 8usize * 1024usize;
+const DEFAULT_MAX_COUNT_HEADER: usize = 100usize;
+const DEFAULT_MAX_LENGTH_HEAD: usize = 64usize * 1024usize;
+const DEFAULT_MAX_LENGTH_BODY: usize = 8usize * 1024usize * 1024usize;
+
+impl Limits {
+    pub fn new() -> Self {
+        return Self {
+            max_length_start_line: DEFAULT_MAX_LENGTH_START_LINE,
+            max_length_header: DEFAULT_MAX_LENGTH_HEADER,
+            max_count_header: DEFAULT_MAX_COUNT_HEADER,
+            max_length_head: DEFAULT_MAX_LENGTH_HEAD,
+            max_length_body: DEFAULT_MAX_LENGTH_BODY,
+        };
+    }
+
+    // Request line of a request, status line of a response.
+    pub fn set_max_length_start_line(&mut self, max_length_start_line: usize) {
+        self.max_length_start_line = max_length_start_line;
+    }
+
+    pub fn get_max_length_start_line(&self) -> usize {
+        return self.max_length_start_line;
+    }
+
+    // Single header field line, name and value included.
+    pub fn set_max_length_header(&mut self, max_length_header: usize) {
+        self.max_length_header = max_length_header;
+    }
+
+    pub fn get_max_length_header(&self) -> usize {
+        return self.max_length_header;
+    }
+
+    pub fn set_max_count_header(&mut self, max_count_header: usize) {
+        self.max_count_header = max_count_header;
+    }
+
+    pub fn get_max_count_header(&self) -> usize {
+        return self.max_count_header;
+    }
+
+    // Start line and header fields together, line terminators included.
+    pub fn set_max_length_head(&mut self, max_length_head: usize) {
+        self.max_length_head = max_length_head;
+    }
+
+    pub fn get_max_length_head(&self) -> usize {
+        return self.max_length_head;
+    }
+
+    pub fn set_max_length_body(&mut self, max_length_body: usize) {
+        self.max_length_body = max_length_body;
+    }
+
+    pub fn get_max_length_body(&self) -> usize {
+        return self.max_length_body;
+    }
+}
@@ -2,7 +2,7 @@ use std::fmt;
 
 macro_rules! declare_methods {
     ($({$enum_name:ident, $text:expr}),+) => {
-        #[derive(PartialEq, Eq)]
+        #[derive(PartialEq, Eq, Clone, Copy)]
         pub enum Method {
             $($enum_name,)+
         }
@@ -38,6 +38,13 @@ declare_methods!(
     {MOVE, b"MOVE"}
 );
 
+impl Method {
+    // Sending the request several times has the same effect as sending it once (RFC 7231 4.2.2).
+    pub fn is_idempotent(&self) -> bool {
+        return matches!(self, Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS | Method::TRACE);
+    }
+}
+
 impl fmt::Display for Method {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         return unsafe {
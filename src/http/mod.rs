@@ -1,9 +1,19 @@
 pub mod method;
 pub mod status;
+pub mod headers;
+pub mod limits;
+pub mod url;
 
 pub mod server;
 pub mod client;
 
+pub mod codec;
+
+mod date;
+mod json;
+mod diagnostics;
+mod request_id;
+
 const MIN_LENGTH_METHOD: usize = 3usize; // "GET", "PUT", ...
 const MIN_LENGTH_TARGET: usize = 1usize; // "/"
 
@@ -13,6 +23,9 @@ const LENGTH_EOL: usize = 2usize; // "\r\n"
 
 pub use self::method::Method;
 pub use self::status::Status;
+pub use self::headers::Headers;
+pub use self::limits::Limits;
+pub use self::url::Url;
 
 pub use self::client::Client;
 pub use self::server::Server;
@@ -26,11 +39,59 @@ pub enum Error {
     InvalidAddress,
 
     InvalidPath,
+    InvalidBody,
+
+    InvalidUrl,
+    UnsupportedScheme,
 
     UnsupportedProtocolVersion,
 
     BadRequest,
     BadResponse,
+
+    TooManyRedirects,
+    TunnelFailed,
+
+    URITooLong,
+    HeaderFieldsTooLarge,
+    PayloadTooLarge,
+}
+
+impl Error {
+    // Name of the variant, as a stable label for metrics.
+    pub fn get_name(&self) -> &'static str {
+        return match self {
+            Error::InternalError(_) => "InternalError",
+            Error::InvalidPort => "InvalidPort",
+            Error::InvalidAddress => "InvalidAddress",
+            Error::InvalidPath => "InvalidPath",
+            Error::InvalidBody => "InvalidBody",
+            Error::InvalidUrl => "InvalidUrl",
+            Error::UnsupportedScheme => "UnsupportedScheme",
+            Error::UnsupportedProtocolVersion => "UnsupportedProtocolVersion",
+            Error::BadRequest => "BadRequest",
+            Error::BadResponse => "BadResponse",
+            Error::TooManyRedirects => "TooManyRedirects",
+            Error::TunnelFailed => "TunnelFailed",
+            Error::URITooLong => "URITooLong",
+            Error::HeaderFieldsTooLarge => "HeaderFieldsTooLarge",
+            Error::PayloadTooLarge => "PayloadTooLarge",
+        };
+    }
+
+    // Status of the response answering a request that failed with this error.
+    // Errors of an upstream exchange, as when proxying, are the gateway's fault.
+    pub fn get_status(&self) -> Status {
+        return match self {
+            Error::InternalError(_) | Error::InvalidPort | Error::InvalidAddress => Status::InternalServerError,
+            Error::InvalidPath | Error::InvalidBody | Error::InvalidUrl | Error::UnsupportedScheme | Error::BadRequest => Status::BadRequest,
+            Error::UnsupportedProtocolVersion => Status::HTTPVersionNotSupported,
+            Error::BadResponse | Error::TooManyRedirects | Error::TunnelFailed => Status::BadGateway,
+            Error::URITooLong => Status::URITooLong,
+            Error::HeaderFieldsTooLarge => Status::RequestHeaderFieldsTooLarge,
+            Error::PayloadTooLarge => Status::PayloadTooLarge,
+        };
+    }
 }
 
 impl From<std::io::Error> for Error {
@@ -52,9 +113,17 @@ impl fmt::Debug for Error {
             Error::InvalidPort => formatter.write_str("Invalid Port"),
             Error::InvalidAddress => formatter.write_str("Invalid Address"),
             Error::InvalidPath => formatter.write_str("Invalid Path"),
+            Error::InvalidBody => formatter.write_str("Invalid Body"),
+            Error::InvalidUrl => formatter.write_str("Invalid URL"),
+            Error::UnsupportedScheme => formatter.write_str("Unsupported Scheme"),
             Error::UnsupportedProtocolVersion => formatter.write_str("Unsupported Protocol Version"),
             Error::BadRequest => formatter.write_str("Bad Request"),
             Error::BadResponse => formatter.write_str("Bad Response"),
+            Error::TooManyRedirects => formatter.write_str("Too Many Redirects"),
+            Error::TunnelFailed => formatter.write_str("Tunnel Failed"),
+            Error::URITooLong => formatter.write_str("URI Too Long"),
+            Error::HeaderFieldsTooLarge => formatter.write_str("Header Fields Too Large"),
+            Error::PayloadTooLarge => formatter.write_str("Payload Too Large"),
         };
     }
 }
@@ -66,9 +135,17 @@ impl std::fmt::Display for Error {
             Error::InvalidPort => formatter.write_str("Invalid Port"),
             Error::InvalidAddress => formatter.write_str("Invalid Address"),
             Error::InvalidPath => formatter.write_str("Invalid Path"),
+            Error::InvalidBody => formatter.write_str("Invalid Body"),
+            Error::InvalidUrl => formatter.write_str("Invalid URL"),
+            Error::UnsupportedScheme => formatter.write_str("Unsupported Scheme"),
             Error::UnsupportedProtocolVersion => formatter.write_str("Unsupported Protocol Version"),
             Error::BadRequest => formatter.write_str("Bad Request"),
             Error::BadResponse => formatter.write_str("Bad Response"),
+            Error::TooManyRedirects => formatter.write_str("Too Many Redirects"),
+            Error::TunnelFailed => formatter.write_str("Tunnel Failed"),
+            Error::URITooLong => formatter.write_str("URI Too Long"),
+            Error::HeaderFieldsTooLarge => formatter.write_str("Header Fields Too Large"),
+            Error::PayloadTooLarge => formatter.write_str("Payload Too Large"),
         };
     }
 }
@@ -83,3 +160,27 @@ impl std::error::Error for Error {
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    mod assertions {
+        use std::io;
+
+        use super::super::Error;
+        use super::super::Status;
+
+        #[test]
+        fn status() {
+            let internal = Error::from(io::Error::from(io::ErrorKind::ConnectionReset));
+
+            assert_eq!(internal.get_status(), Status::InternalServerError);
+            assert_eq!(Error::BadRequest.get_status(), Status::BadRequest);
+            assert_eq!(Error::InvalidPath.get_status(), Status::BadRequest);
+            assert_eq!(Error::PayloadTooLarge.get_status(), Status::PayloadTooLarge);
+            assert_eq!(Error::URITooLong.get_status(), Status::URITooLong);
+            assert_eq!(Error::HeaderFieldsTooLarge.get_status(), Status::RequestHeaderFieldsTooLarge);
+            assert_eq!(Error::UnsupportedProtocolVersion.get_status(), Status::HTTPVersionNotSupported);
+            assert_eq!(Error::TooManyRedirects.get_status(), Status::BadGateway);
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use std::cell::RefCell;
+
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::hash::Hasher;
+
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+pub(crate) const HEADER_REQUEST_ID: &str = "X-Request-Id";
+
+const MAX_LENGTH_REQUEST_ID: usize = 200usize;
+
+static COUNTER: AtomicU64 = AtomicU64::new(0u64);
+
+thread_local! {
+    // ID of the request the thread is handling, for `Client` to forward.
+    static CURRENT: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+fn get_random() -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+
+    // The counter keeps IDs apart should two hashers ever be seeded alike.
+    hasher.write_u64(COUNTER.fetch_add(1u64, Ordering::Relaxed));
+    hasher.write_u128(SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos());
+
+    return hasher.finish();
+}
+
+// Random (version 4) UUID, such as "f47ac10b-58cc-4372-a567-0e02b2c3d479".
+pub(crate) fn generate() -> String {
+    let high = get_random() & !0xF000u64 | 0x4000u64;
+    let low = get_random() & !(0xC000u64 << 48u32) | 0x8000u64 << 48u32;
+
+    return format!("{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+        high >> 32u32, high >> 16u32 & 0xFFFFu64, high & 0xFFFFu64, low >> 48u32, low & 0xFFFF_FFFF_FFFFu64);
+}
+
+// Whether a received ID can be kept: short, and made of visible ASCII only, so it cannot inject into headers or logs.
+pub(crate) fn is_valid(id: &str) -> bool {
+    return !id.is_empty() && id.len() <= MAX_LENGTH_REQUEST_ID && id.bytes().all(|byte| byte.is_ascii_graphic());
+}
+
+pub(crate) fn get_current() -> Option<String> {
+    return CURRENT.with(|current| current.borrow().clone());
+}
+
+// Makes `id` the current request ID of the thread until dropped, restoring the previous one.
+pub(crate) struct Scope {
+    previous: Option<String>,
+}
+
+impl Scope {
+    pub(crate) fn enter(id: &str) -> Self {
+        return Self {
+            previous: CURRENT.with(|current| current.replace(Some(String::from(id)))),
+        };
+    }
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+
+        CURRENT.with(|current| *current.borrow_mut() = previous);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod assertions {
+        use super::super::generate;
+        use super::super::is_valid;
+        use super::super::get_current;
+        use super::super::Scope;
+
+        #[test]
+        fn generate_valid() {
+            let first = generate();
+            let second = generate();
+
+            assert_ne!(first, second);
+            assert_eq!(first.len(), 36usize);
+            assert_eq!(&first[14usize..15usize], "4");
+            assert!(is_valid(&first));
+
+            assert!(!is_valid(""));
+            assert!(!is_valid("a b"));
+            assert!(!is_valid("a\r\nSet-Cookie: x=1"));
+            assert!(!is_valid(&"a".repeat(201usize)));
+        }
+
+        #[test]
+        fn scope() {
+            assert_eq!(get_current(), None);
+
+            {
+                let _outer = Scope::enter("outer");
+
+                {
+                    let _inner = Scope::enter("inner");
+
+                    assert_eq!(get_current().as_deref(), Some("inner"));
+                }
+
+                assert_eq!(get_current().as_deref(), Some("outer"));
+            }
+
+            assert_eq!(get_current(), None);
+        }
+    }
+}
@@ -0,0 +1,413 @@
+use std::io;
+use std::io::Write;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use std::net::SocketAddr;
+
+use std::sync::Mutex;
+
+use std::time::Duration;
+use std::time::SystemTime;
+
+use crate::http::Method;
+use crate::http::Status;
+
+use crate::http::date;
+use crate::http::json;
+
+use super::Result;
+use super::Request;
+use super::Response;
+
+// What is known of one exchange once its response was written.
+pub struct AccessEntry {
+    address: Option<SocketAddr>,
+
+    // None when the request could not be parsed.
+    request_id: Option<String>,
+
+    // When the connection was accepted.
+    time: SystemTime,
+
+    // None when the request could not be parsed.
+    method: Option<Method>,
+    target: Option<String>,
+
+    referer: Option<String>,
+    user_agent: Option<String>,
+
+    status: Status,
+
+    // Bytes of body sent, without the head.
+    bytes_sent: usize,
+
+    latency: Duration,
+}
+
+impl AccessEntry {
+    pub(crate) fn new(address: Option<SocketAddr>, time: SystemTime, request: Option<&Request>, response: &Response, bytes_sent: usize, latency: Duration) -> Self {
+        let header = |name: &str| request.and_then(|request| request.get_headers().get(name)).map(String::from);
+
+        return Self {
+            address,
+
+            request_id: request.map(|request| String::from(request.get_id())),
+
+            time,
+
+            method: request.map(|request| *request.get_method()),
+            target: request.map(|request| String::from(request.get_target())),
+
+            referer: header("Referer"),
+            user_agent: header("User-Agent"),
+
+            status: *response.get_status(),
+
+            bytes_sent,
+
+            latency,
+        };
+    }
+
+    pub fn get_address(&self) -> Option<SocketAddr> {
+        return self.address;
+    }
+
+    pub fn get_request_id(&self) -> Option<&str> {
+        return self.request_id.as_deref();
+    }
+
+    pub fn get_time(&self) -> SystemTime {
+        return self.time;
+    }
+
+    pub fn get_method(&self) -> Option<&Method> {
+        return self.method.as_ref();
+    }
+
+    pub fn get_target(&self) -> Option<&str> {
+        return self.target.as_deref();
+    }
+
+    pub fn get_referer(&self) -> Option<&str> {
+        return self.referer.as_deref();
+    }
+
+    pub fn get_user_agent(&self) -> Option<&str> {
+        return self.user_agent.as_deref();
+    }
+
+    pub fn get_status(&self) -> &Status {
+        return &self.status;
+    }
+
+    pub fn get_bytes_sent(&self) -> usize {
+        return self.bytes_sent;
+    }
+
+    pub fn get_latency(&self) -> Duration {
+        return self.latency;
+    }
+
+    fn get_request_line(&self) -> Option<String> {
+        return match (&self.method, &self.target) {
+            (Some(method), Some(target)) => Some(format!("{} {} HTTP/1.1", String::from_utf8_lossy(method.get_text()), target)),
+            _ => None,
+        };
+    }
+}
+
+// Escapes what would break out of a quoted field, as Apache does.
+fn escape_quoted(value: &str) -> String {
+    let mut output = String::with_capacity(value.len());
+
+    for character in value.chars() {
+        match character {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            character if character.is_control() => output.push_str(&format!("\\x{:02x}", character as u32)),
+            character => output.push(character),
+        }
+    }
+
+    return output;
+}
+
+fn to_json(value: Option<&str>) -> String {
+    return match value {
+        Some(value) => json::escape(value),
+        None => String::from("null"),
+    };
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum LogFormat {
+    // host ident user [time] "request line" status bytes
+    Common,
+    // Common, followed by "referer" "user agent".
+    Combined,
+    // One JSON object per line, with the request ID the other formats have no field for.
+    Json,
+}
+
+impl LogFormat {
+    // The line for `entry`, without its line feed.
+    pub fn format(&self, entry: &AccessEntry) -> String {
+        let address = entry.address.map(|address| address.ip().to_string());
+
+        if *self == LogFormat::Json {
+            return format!("{{\"time\":\"{}\",\"address\":{},\"method\":{},\"target\":{},\"status\":{},\"bytes_sent\":{},\"latency_us\":{},\"referer\":{},\"user_agent\":{},\"request_id\":{}}}",
+                date::format_rfc3339_date(entry.time),
+                to_json(address.as_deref()),
+                to_json(entry.method.as_ref().map(|method| String::from_utf8_lossy(method.get_text())).as_deref()),
+                to_json(entry.target.as_deref()),
+                entry.status.get_code(),
+                entry.bytes_sent,
+                entry.latency.as_micros(),
+                to_json(entry.referer.as_deref()),
+                to_json(entry.user_agent.as_deref()),
+                to_json(entry.request_id.as_deref()));
+        }
+
+        let bytes_sent = match entry.bytes_sent {
+            0usize => String::from("-"),
+            bytes_sent => bytes_sent.to_string(),
+        };
+
+        let mut line = format!("{} - - [{}] \"{}\" {} {}",
+            address.as_deref().unwrap_or("-"),
+            date::format_log_date(entry.time),
+            entry.get_request_line().map(|line| escape_quoted(&line)).as_deref().unwrap_or("-"),
+            entry.status.get_code(),
+            bytes_sent);
+
+        if *self == LogFormat::Combined {
+            line.push_str(&format!(" \"{}\" \"{}\"",
+                entry.referer.as_deref().map(escape_quoted).as_deref().unwrap_or("-"),
+                entry.user_agent.as_deref().map(escape_quoted).as_deref().unwrap_or("-")));
+        }
+
+        return line;
+    }
+}
+
+// Receives an entry for every exchange `Server` completes.
+pub trait AccessLogger {
+    fn log(&self, entry: &AccessEntry);
+}
+
+impl<F> AccessLogger for F where F: Fn(&AccessEntry) {
+    fn log(&self, entry: &AccessEntry) {
+        self(entry);
+    }
+}
+
+// Writes entries as lines in one of the log formats.
+pub struct AccessLog {
+    format: LogFormat,
+
+    writer: Mutex<Box<dyn Write + Send>>,
+}
+
+impl AccessLog {
+    pub fn new<W: Write + Send + 'static>(format: LogFormat, writer: W) -> Self {
+        return Self {
+            format,
+
+            writer: Mutex::new(Box::new(writer)),
+        };
+    }
+
+    // Appends to the file at `path`, creating it if needed.
+    pub fn to_file<P: AsRef<Path>>(format: LogFormat, path: P) -> Result<Self> {
+        let file = fs::OpenOptions::new().create(true).append(true).open(path)?;
+
+        return Ok(Self::new(format, file));
+    }
+
+    pub fn get_format(&self) -> LogFormat {
+        return self.format;
+    }
+}
+
+impl AccessLogger for AccessLog {
+    fn log(&self, entry: &AccessEntry) {
+        let mut line = self.format.format(entry);
+        line.push('\n');
+
+        let mut writer = self.writer.lock().unwrap_or_else(|error| error.into_inner());
+
+        // Failing to log must not fail the exchange, which already completed.
+        let _ = writer.write_all(line.as_bytes()).and_then(|_| writer.flush());
+    }
+}
+
+// File that is rotated once it would grow past a size: "access.log" is renamed to "access.log.1",
+// the previous "access.log.1" to "access.log.2", and so on, keeping at most `max_files` of them.
+pub struct RotatingFile {
+    path: PathBuf,
+
+    max_size: u64,
+    max_files: usize,
+
+    file: fs::File,
+    size: u64,
+}
+
+impl RotatingFile {
+    pub fn open<P: AsRef<Path>>(path: P, max_size: u64, max_files: usize) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+
+        let file = fs::OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+
+        return Ok(Self {
+            path,
+
+            max_size,
+            max_files,
+
+            file,
+            size,
+        });
+    }
+
+    pub fn get_path(&self) -> &Path {
+        return &self.path;
+    }
+
+    pub fn get_max_size(&self) -> u64 {
+        return self.max_size;
+    }
+
+    pub fn get_max_files(&self) -> usize {
+        return self.max_files;
+    }
+
+    fn get_rotated_path(&self, index: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{}", index));
+
+        return PathBuf::from(path);
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+
+        if self.max_files > 0usize {
+            for index in (1usize..self.max_files).rev() {
+                match fs::rename(self.get_rotated_path(index), self.get_rotated_path(index + 1usize)) {
+                    Err(error) if error.kind() != io::ErrorKind::NotFound => return Err(error),
+                    _ => {}
+                }
+            }
+
+            fs::rename(&self.path, self.get_rotated_path(1usize))?;
+        }
+
+        self.file = fs::OpenOptions::new().create(true).write(true).truncate(true).open(&self.path)?;
+        self.size = 0u64;
+
+        return Ok(());
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buffer: &[u8]) -> io::Result<usize> {
+        if self.size > 0u64 && self.size + buffer.len() as u64 > self.max_size {
+            self.rotate()?;
+        }
+
+        let length = self.file.write(buffer)?;
+        self.size += length as u64;
+
+        return Ok(length);
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        return self.file.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod assertions {
+        use std::io::Write;
+
+        use std::env;
+        use std::fs;
+        use std::process;
+
+        use std::time::Duration;
+        use std::time::UNIX_EPOCH;
+
+        use crate::http::Status;
+
+        use super::super::Request;
+        use super::super::Response;
+        use super::super::AccessEntry;
+        use super::super::LogFormat;
+        use super::super::RotatingFile;
+
+        #[test]
+        fn format() {
+            let mut request = Request::parse(&mut "GET /index.html?q=\"x\" HTTP/1.1\r\nReferer: http://example.com/\r\nUser-Agent: curl/8.0\r\n\r\n".as_bytes()).ok().unwrap();
+            request.set_id(String::from("7f3c2a"));
+
+            let mut response = Response::new();
+            response.set_status(Status::NotFound);
+
+            let time = UNIX_EPOCH + Duration::from_secs(971186136u64);
+            let address = "127.0.0.1:50000".parse().ok();
+
+            let entry = AccessEntry::new(address, time, Some(&request), &response, 2326usize, Duration::from_micros(1500u64));
+
+            assert_eq!(LogFormat::Common.format(&entry), "127.0.0.1 - - [10/Oct/2000:13:55:36 +0000] \"GET /index.html?q=\\\"x\\\" HTTP/1.1\" 404 2326");
+            assert_eq!(LogFormat::Combined.format(&entry), "127.0.0.1 - - [10/Oct/2000:13:55:36 +0000] \"GET /index.html?q=\\\"x\\\" HTTP/1.1\" 404 2326 \"http://example.com/\" \"curl/8.0\"");
+            assert_eq!(LogFormat::Json.format(&entry), "{\"time\":\"2000-10-10T13:55:36.000Z\",\"address\":\"127.0.0.1\",\"method\":\"GET\",\"target\":\"/index.html?q=\\\"x\\\"\",\"status\":404,\"bytes_sent\":2326,\"latency_us\":1500,\"referer\":\"http://example.com/\",\"user_agent\":\"curl/8.0\",\"request_id\":\"7f3c2a\"}");
+
+            let entry = AccessEntry::new(None, time, None, &response, 0usize, Duration::from_micros(1500u64));
+
+            assert_eq!(LogFormat::Common.format(&entry), "- - - [10/Oct/2000:13:55:36 +0000] \"-\" 404 -");
+        }
+
+        #[test]
+        fn rotation() {
+            let directory = env::temp_dir().join(format!("bwf-rotation-{}", process::id()));
+            fs::create_dir_all(&directory).ok().unwrap();
+
+            let path = directory.join("access.log");
+            let read = |name: &str| fs::read_to_string(directory.join(name)).ok();
+
+            let mut file = RotatingFile::open(&path, 10u64, 2usize).ok().unwrap();
+
+            for line in ["line1\n", "line2\n", "line3\n", "line4\n"] {
+                file.write_all(line.as_bytes()).ok().unwrap();
+            }
+
+            file.flush().ok().unwrap();
+
+            assert_eq!(read("access.log").as_deref(), Some("line4\n"));
+            assert_eq!(read("access.log.1").as_deref(), Some("line3\n"));
+            assert_eq!(read("access.log.2").as_deref(), Some("line2\n"));
+            assert_eq!(read("access.log.3"), None);
+
+            // Opened again, the size of what is already there counts.
+            let mut file = RotatingFile::open(&path, 10u64, 2usize).ok().unwrap();
+            file.write_all(b"line5\n").ok().unwrap();
+
+            assert_eq!(read("access.log.1").as_deref(), Some("line4\n"));
+
+            // Without rotated files, the file starts over.
+            let mut file = RotatingFile::open(&path, 10u64, 0usize).ok().unwrap();
+            file.write_all(b"line6\n").ok().unwrap();
+
+            assert_eq!(read("access.log").as_deref(), Some("line6\n"));
+            assert_eq!(read("access.log.1").as_deref(), Some("line4\n"));
+
+            fs::remove_dir_all(&directory).ok().unwrap();
+        }
+    }
+}
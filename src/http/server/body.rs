@@ -0,0 +1,146 @@
+use std::io;
+
+use crate::http::Status;
+use crate::http::Headers;
+
+use crate::http::codec;
+use crate::http::codec::Event;
+use crate::http::codec::RequestDecoder;
+
+const LENGTH_READ: usize = 4096usize;
+
+// Request body, decoded from its Content-Length or chunked framing as the handler reads it.
+pub struct Body {
+    decoder: Option<RequestDecoder>,
+
+    reader: Option<Box<dyn io::Read>>,
+    interim: Option<Box<dyn io::Write>>,
+
+    // Decoded data that did not fit in the caller's buffer.
+    pending: Vec<u8>,
+    pending_finger: usize,
+
+    content_length: Option<usize>,
+    chunked: bool,
+
+    done: bool,
+}
+
+fn into_io_error(error: crate::http::Error) -> io::Error {
+    return io::Error::new(io::ErrorKind::InvalidData, error.to_string());
+}
+
+impl Body {
+    pub(crate) fn new(content_length: Option<usize>, chunked: bool) -> Self {
+        return Self {
+            decoder: None,
+
+            reader: None,
+            interim: None,
+
+            pending: Vec::new(),
+            pending_finger: 0usize,
+
+            content_length,
+            chunked,
+
+            done: false,
+        };
+    }
+
+    // Takes over the decoder that parsed the head, along with whatever part of the body it already buffered.
+    pub(crate) fn set_decoder(&mut self, decoder: RequestDecoder) {
+        self.decoder = Some(decoder);
+    }
+
+    // Attaches the connection the rest of the body is read from.
+    // When `interim` is set, "100 Continue" is written to it right before the first read.
+    pub(crate) fn attach(&mut self, reader: Box<dyn io::Read>, interim: Option<Box<dyn io::Write>>) {
+        self.reader = Some(reader);
+        self.interim = interim;
+    }
+
+    pub fn is_chunked(&self) -> bool {
+        return self.chunked;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return !self.chunked && self.content_length.unwrap_or(0usize) == 0usize;
+    }
+
+    pub fn read_to_vec(&mut self) -> io::Result<Vec<u8>> {
+        let mut body = Vec::new();
+
+        io::Read::read_to_end(self, &mut body)?;
+
+        return Ok(body);
+    }
+}
+
+impl io::Read for Body {
+    fn read(&mut self, output: &mut [u8]) -> io::Result<usize> {
+        if output.is_empty() {
+            return Ok(0usize);
+        }
+
+        if self.pending_finger < self.pending.len() {
+            let length = std::cmp::min(output.len(), self.pending.len() - self.pending_finger);
+
+            output[..length].copy_from_slice(&self.pending[self.pending_finger..self.pending_finger + length]);
+            self.pending_finger += length;
+
+            return Ok(length);
+        }
+
+        if let Some(mut interim) = self.interim.take() {
+            if !self.is_empty() {
+                let mut head = Vec::new();
+                codec::encode_response_head(&mut head, &Status::Continue, &Headers::new());
+
+                interim.write_all(&head)?;
+                interim.flush()?;
+            }
+        }
+
+        let decoder = match &mut self.decoder {
+            Some(decoder) => decoder,
+            None => return Ok(0usize),
+        };
+
+        while !self.done {
+            match decoder.poll().map_err(into_io_error)? {
+                Some(Event::Data(data)) => {
+                    let length = std::cmp::min(output.len(), data.len());
+
+                    output[..length].copy_from_slice(&data[..length]);
+
+                    self.pending.clear();
+                    self.pending.extend_from_slice(&data[length..]);
+                    self.pending_finger = 0usize;
+
+                    return Ok(length);
+                }
+                Some(Event::End) => {
+                    self.done = true;
+                }
+                Some(Event::Head(_)) => {
+                    return Err(io::Error::from(io::ErrorKind::InvalidData));
+                }
+                None => {
+                    let length = match &mut self.reader {
+                        Some(reader) => reader.read(decoder.prepare(LENGTH_READ))?,
+                        None => 0usize,
+                    };
+
+                    if length == 0usize {
+                        decoder.feed_eof();
+                    } else {
+                        decoder.commit(length);
+                    }
+                }
+            }
+        }
+
+        return Ok(0usize);
+    }
+}
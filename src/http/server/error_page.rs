@@ -0,0 +1,37 @@
+use super::Request;
+use super::Response;
+use super::Problem;
+
+use super::problem;
+
+// Gives a body to error responses (4xx and 5xx) that have none, see `Server::set_error_renderer`.
+// The request is None when it could not be parsed.
+pub trait ErrorRenderer {
+    fn render(&self, request: Option<&Request>, response: &mut Response);
+}
+
+impl<F> ErrorRenderer for F where F: Fn(Option<&Request>, &mut Response) {
+    fn render(&self, request: Option<&Request>, response: &mut Response) {
+        self(request, response);
+    }
+}
+
+// Minimal HTML page naming the status.
+pub fn render_html(_: Option<&Request>, response: &mut Response) {
+    let phrase = String::from_utf8_lossy(response.get_status().get_phrase()).into_owned();
+
+    response.set_header("Content-Type", "text/html; charset=utf-8");
+    response.set_body(format!("<!DOCTYPE html>\n<html>\n<head><title>{0}</title></head>\n<body><h1>{0}</h1></body>\n</html>\n", phrase));
+}
+
+// Problem details (RFC 7807) with no more than the status, see `Problem`.
+pub fn render_problem(request: Option<&Request>, response: &mut Response) {
+    let mut problem = Problem::new(*response.get_status());
+
+    if let Some(request) = request {
+        problem.set_instance(request.get_path());
+    }
+
+    response.set_header("Content-Type", problem::CONTENT_TYPE_PROBLEM);
+    response.set_body(problem.to_json());
+}
@@ -0,0 +1,440 @@
+use std::io;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use std::time::Duration;
+use std::time::UNIX_EPOCH;
+
+use crate::http::Method;
+use crate::http::Status;
+
+use crate::http::date;
+use crate::http::url;
+
+use super::Handler;
+use super::Request;
+use super::Response;
+
+const CONTENT_TYPE_DEFAULT: &str = "application/octet-stream";
+
+// Content types by file extension, which is compared ignoring case.
+const CONTENT_TYPES: [(&str, &str); 38usize] = [
+    ("html", "text/html; charset=utf-8"),
+    ("htm", "text/html; charset=utf-8"),
+    ("css", "text/css; charset=utf-8"),
+    ("js", "text/javascript; charset=utf-8"),
+    ("mjs", "text/javascript; charset=utf-8"),
+    ("json", "application/json"),
+    ("map", "application/json"),
+    ("xml", "application/xml"),
+    ("txt", "text/plain; charset=utf-8"),
+    ("md", "text/markdown; charset=utf-8"),
+    ("csv", "text/csv; charset=utf-8"),
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("webp", "image/webp"),
+    ("avif", "image/avif"),
+    ("svg", "image/svg+xml"),
+    ("ico", "image/vnd.microsoft.icon"),
+    ("bmp", "image/bmp"),
+    ("woff", "font/woff"),
+    ("woff2", "font/woff2"),
+    ("ttf", "font/ttf"),
+    ("otf", "font/otf"),
+    ("mp3", "audio/mpeg"),
+    ("ogg", "audio/ogg"),
+    ("wav", "audio/wav"),
+    ("mp4", "video/mp4"),
+    ("webm", "video/webm"),
+    ("pdf", "application/pdf"),
+    ("zip", "application/zip"),
+    ("gz", "application/gzip"),
+    ("tar", "application/x-tar"),
+    ("wasm", "application/wasm"),
+    ("webmanifest", "application/manifest+json"),
+    ("rss", "application/rss+xml"),
+    ("atom", "application/atom+xml"),
+    ("yaml", "application/yaml"),
+];
+
+// Content type of a file by its extension, "application/octet-stream" when not known.
+pub fn get_content_type<P: AsRef<Path>>(path: P) -> &'static str {
+    let extension = match path.as_ref().extension().and_then(|extension| extension.to_str()) {
+        Some(extension) => extension,
+        None => return CONTENT_TYPE_DEFAULT,
+    };
+
+    return CONTENT_TYPES.iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(extension))
+        .map(|(_, content_type)| *content_type)
+        .unwrap_or(CONTENT_TYPE_DEFAULT);
+}
+
+fn escape_html(value: &str) -> String {
+    let mut output = String::with_capacity(value.len());
+
+    for character in value.chars() {
+        match character {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            '\'' => output.push_str("&#39;"),
+            character => output.push(character),
+        }
+    }
+
+    return output;
+}
+
+fn get_status_response(status: Status) -> Response {
+    let mut response = Response::new();
+    response.set_status(status);
+
+    return response;
+}
+
+// Serves the files under a directory, as a handler or a route of a `Pipeline`.
+pub struct StaticFiles {
+    root: PathBuf,
+
+    // Part of the request path that is not part of the file path, such as "/static".
+    prefix: String,
+
+    // Served in place of a directory, the first one that exists.
+    index_files: Vec<String>,
+
+    // Whether directories without an index file are listed, or forbidden.
+    listing: bool,
+}
+
+impl StaticFiles {
+    pub fn new<P: AsRef<Path>>(root: P) -> Self {
+        return Self {
+            root: root.as_ref().to_path_buf(),
+
+            prefix: String::new(),
+
+            index_files: vec![String::from("index.html")],
+
+            listing: false,
+        };
+    }
+
+    pub fn get_root(&self) -> &Path {
+        return &self.root;
+    }
+
+    pub fn set_prefix(&mut self, prefix: &str) {
+        self.prefix = String::from(prefix.trim_end_matches('/'));
+    }
+
+    pub fn get_prefix(&self) -> &str {
+        return &self.prefix;
+    }
+
+    pub fn set_index_files(&mut self, index_files: &[&str]) {
+        self.index_files = index_files.iter().map(|index_file| String::from(*index_file)).collect();
+    }
+
+    pub fn get_index_files(&self) -> &[String] {
+        return &self.index_files;
+    }
+
+    pub fn set_listing(&mut self, listing: bool) {
+        self.listing = listing;
+    }
+
+    pub fn is_listing(&self) -> bool {
+        return self.listing;
+    }
+
+    // Path under the root that a request path names, None when outside of the prefix or trying to leave the root.
+    // Nothing is read from the file system yet, symbolic links are checked once the path is resolved.
+    fn get_file_path(&self, path: &str) -> Option<PathBuf> {
+        let path = path.strip_prefix(self.prefix.as_str())?;
+
+        if !path.is_empty() && !path.starts_with('/') {
+            return None;
+        }
+
+        let mut file_path = self.root.clone();
+
+        for segment in path.split('/') {
+            let segment = url::decode_percent(segment);
+
+            match segment.as_str() {
+                "" | "." => continue,
+                ".." => return None,
+                segment if segment.contains(&['/', '\\', '\0'][..]) => return None,
+                segment => file_path.push(segment),
+            }
+        }
+
+        return Some(file_path);
+    }
+
+    // The resolved path when it exists and is still under the root, once symbolic links are followed.
+    fn resolve(&self, file_path: &Path) -> io::Result<PathBuf> {
+        let root = self.root.canonicalize()?;
+        let resolved = file_path.canonicalize()?;
+
+        if !resolved.starts_with(&root) {
+            return Err(io::Error::from(io::ErrorKind::NotFound));
+        }
+
+        return Ok(resolved);
+    }
+
+    fn get_listing(&self, request: &Request, directory: &Path) -> io::Result<Response> {
+        let mut entries = Vec::new();
+
+        for entry in fs::read_dir(directory)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+
+            entries.push((entry.file_type()?.is_dir(), name));
+        }
+
+        entries.sort_by(|(_, left), (_, right)| left.cmp(right));
+
+        // The request path comes without its trailing slash, links are made absolute from it.
+        let path = request.get_path().trim_end_matches('/');
+
+        let title = escape_html(&url::decode_percent(if path.is_empty() { "/" } else { path }));
+        let mut body = format!("<!DOCTYPE html>\n<html>\n<head><title>Index of {0}</title></head>\n<body>\n<h1>Index of {0}</h1>\n<ul>\n", title);
+
+        if path != self.prefix {
+            let parent = &path[..path.rfind('/').unwrap_or(0usize)];
+
+            body.push_str(&format!("<li><a href=\"{}/\">../</a></li>\n", escape_html(parent)));
+        }
+
+        for (is_directory, name) in entries.iter() {
+            let suffix = if *is_directory { "/" } else { "" };
+
+            body.push_str(&format!("<li><a href=\"{}/{}\">{}{}</a></li>\n", escape_html(path), url::encode_percent(name), escape_html(name), suffix));
+        }
+
+        body.push_str("</ul>\n</body>\n</html>\n");
+
+        let mut response = Response::new();
+        response.set_header("Content-Type", "text/html; charset=utf-8");
+        response.set_body(body);
+
+        return Ok(response);
+    }
+
+    fn get_file(&self, request: &Request, path: &Path, metadata: &fs::Metadata) -> io::Result<Response> {
+        let mut response = Response::new();
+
+        // HTTP dates have no fraction of a second, which must not make the file look modified.
+        let modified = metadata.modified().ok().and_then(|modified| modified.duration_since(UNIX_EPOCH).ok());
+
+        if let Some(modified) = modified {
+            let modified = UNIX_EPOCH + Duration::from_secs(modified.as_secs());
+
+            response.set_header("Last-Modified", &date::format_http_date(modified));
+
+            let since = request.get_headers().get("If-Modified-Since").and_then(date::parse_http_date);
+
+            if since.is_some_and(|since| modified <= since) {
+                response.set_status(Status::NotModified);
+
+                return Ok(response);
+            }
+        }
+
+        let file = fs::File::open(path)?;
+
+        response.set_header("Content-Type", get_content_type(path));
+        response.set_seekable_reader(file, metadata.len() as usize);
+
+        return Ok(response);
+    }
+
+    fn serve(&self, request: &Request) -> io::Result<Response> {
+        let file_path = match self.get_file_path(request.get_path()) {
+            Some(file_path) => file_path,
+            None => return Ok(get_status_response(Status::NotFound)),
+        };
+
+        let path = self.resolve(&file_path)?;
+        let metadata = fs::metadata(&path)?;
+
+        if !metadata.is_dir() {
+            return self.get_file(request, &path, &metadata);
+        }
+
+        // Relative links of an index file or a listing resolve against the directory only with the trailing slash.
+        if !request.has_trailing_slash() && !request.get_path().ends_with('/') {
+            let query = request.get_target().find('?').map_or("", |index| &request.get_target()[index..]);
+
+            let mut response = get_status_response(Status::MovedPermanently);
+            response.set_header("Location", &format!("{}/{}", request.get_path(), query));
+
+            return Ok(response);
+        }
+
+        for index_file in self.index_files.iter() {
+            let index_path = path.join(index_file);
+
+            if let Ok(metadata) = fs::metadata(&index_path) {
+                if metadata.is_file() {
+                    return self.get_file(request, &index_path, &metadata);
+                }
+            }
+        }
+
+        if self.listing {
+            return self.get_listing(request, &path);
+        }
+
+        return Ok(get_status_response(Status::Forbidden));
+    }
+}
+
+impl Handler for StaticFiles {
+    fn handle(&self, request: &mut Request) -> Response {
+        if *request.get_method() != Method::GET && *request.get_method() != Method::HEAD {
+            let mut response = get_status_response(Status::MethodNotAllowed);
+            response.set_header("Allow", "GET, HEAD");
+
+            return response;
+        }
+
+        return match self.serve(request) {
+            Ok(response) => response,
+            Err(error) => get_status_response(match error.kind() {
+                io::ErrorKind::NotFound => Status::NotFound,
+                io::ErrorKind::PermissionDenied => Status::Forbidden,
+                _ => Status::InternalServerError,
+            }),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod assertions {
+        use std::env;
+        use std::fs;
+        use std::process;
+
+        use std::path::PathBuf;
+
+        use crate::http::Status;
+
+        use super::super::Handler;
+        use super::super::Request;
+        use super::super::Response;
+        use super::super::StaticFiles;
+        use super::super::get_content_type;
+
+        fn get_response(files: &StaticFiles, head: &str) -> Response {
+            let mut request = Request::parse(&mut format!("{}\r\n\r\n", head).as_bytes()).ok().unwrap();
+
+            return files.handle(&mut request);
+        }
+
+        fn get_body(response: &mut Response) -> String {
+            let mut body = response.get_body().to_vec();
+            response.write_reader(&mut body).ok().unwrap();
+
+            return String::from_utf8(body).ok().unwrap();
+        }
+
+        #[test]
+        fn file_path() {
+            let mut files = StaticFiles::new("/srv/www");
+            files.set_prefix("/static/");
+
+            assert_eq!(files.get_file_path("/static/css/site.css"), Some(PathBuf::from("/srv/www/css/site.css")));
+            assert_eq!(files.get_file_path("/static/a%20b/./c.txt"), Some(PathBuf::from("/srv/www/a b/c.txt")));
+            assert_eq!(files.get_file_path("/static"), Some(PathBuf::from("/srv/www")));
+            assert_eq!(files.get_file_path("/staticfiles/a.txt"), None);
+            assert_eq!(files.get_file_path("/static/../etc/passwd"), None);
+            assert_eq!(files.get_file_path("/static/%2e%2e/etc/passwd"), None);
+            assert_eq!(files.get_file_path("/static/a%2fb"), None);
+            assert_eq!(files.get_file_path("/static/a%5c..%5cb"), None);
+
+            assert_eq!(get_content_type("site.CSS"), "text/css; charset=utf-8");
+            assert_eq!(get_content_type("archive.bin"), "application/octet-stream");
+            assert_eq!(get_content_type("Makefile"), "application/octet-stream");
+        }
+
+        #[test]
+        fn serve() {
+            let directory = env::temp_dir().join(format!("bwf-files-{}", process::id()));
+            let root = directory.join("www");
+
+            fs::create_dir_all(root.join("docs")).ok().unwrap();
+            fs::create_dir_all(root.join("empty")).ok().unwrap();
+            fs::write(root.join("site.css"), "body {}").ok().unwrap();
+            fs::write(root.join("docs/index.html"), "<h1>docs</h1>").ok().unwrap();
+            fs::write(root.join("docs/index.htm"), "<h1>old docs</h1>").ok().unwrap();
+            fs::write(root.join("empty/a&b.txt"), "a and b").ok().unwrap();
+            fs::write(directory.join("secret.txt"), "secret").ok().unwrap();
+
+            let mut files = StaticFiles::new(&root);
+
+            let mut response = get_response(&files, "GET /site.css HTTP/1.1");
+
+            assert_eq!(*response.get_status(), Status::OK);
+            assert_eq!(response.get_headers().get("Content-Type"), Some("text/css; charset=utf-8"));
+            assert_eq!(response.get_content_length(), Some(7usize));
+            assert_eq!(get_body(&mut response), "body {}");
+
+            let last_modified = String::from(response.get_headers().get("Last-Modified").unwrap());
+            let response = get_response(&files, &format!("GET /site.css HTTP/1.1\r\nIf-Modified-Since: {}", last_modified));
+
+            assert_eq!(*response.get_status(), Status::NotModified);
+            assert!(!response.is_streaming());
+
+            // Directories are only served with the trailing slash.
+            let response = get_response(&files, "GET /docs HTTP/1.1");
+
+            assert_eq!(*response.get_status(), Status::MovedPermanently);
+            assert_eq!(response.get_headers().get("Location"), Some("/docs/"));
+            assert_eq!(get_response(&files, "GET /empty?sort=name HTTP/1.1").get_headers().get("Location"), Some("/empty/?sort=name"));
+
+            let mut response = get_response(&files, "GET /docs/ HTTP/1.1");
+
+            assert_eq!(response.get_headers().get("Content-Type"), Some("text/html; charset=utf-8"));
+            assert_eq!(get_body(&mut response), "<h1>docs</h1>");
+
+            files.set_index_files(&["index.htm", "index.html"]);
+
+            assert_eq!(get_body(&mut get_response(&files, "GET /docs/ HTTP/1.1")), "<h1>old docs</h1>");
+
+            // Directories without an index file are only listed when asked to.
+            assert_eq!(*get_response(&files, "GET /empty/ HTTP/1.1").get_status(), Status::Forbidden);
+
+            files.set_listing(true);
+
+            let body = get_body(&mut get_response(&files, "GET /empty/ HTTP/1.1"));
+
+            assert!(body.contains("<li><a href=\"/empty/a%26b.txt\">a&amp;b.txt</a></li>"));
+            assert!(body.contains("<li><a href=\"/\">../</a></li>"));
+
+            #[cfg(unix)]
+            {
+                std::os::unix::fs::symlink(directory.join("secret.txt"), root.join("secret.txt")).ok().unwrap();
+
+                assert_eq!(*get_response(&files, "GET /secret.txt HTTP/1.1").get_status(), Status::NotFound);
+            }
+
+            assert_eq!(*get_response(&files, "GET /missing.txt HTTP/1.1").get_status(), Status::NotFound);
+
+            let response = get_response(&files, "DELETE /site.css HTTP/1.1");
+
+            assert_eq!(*response.get_status(), Status::MethodNotAllowed);
+            assert_eq!(response.get_headers().get("Allow"), Some("GET, HEAD"));
+
+            fs::remove_dir_all(&directory).ok().unwrap();
+        }
+    }
+}
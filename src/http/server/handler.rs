@@ -0,0 +1,12 @@
+use super::Request;
+use super::Response;
+
+pub trait Handler {
+    fn handle(&self, request: &mut Request) -> Response;
+}
+
+impl<F> Handler for F where F: Fn(&mut Request) -> Response {
+    fn handle(&self, request: &mut Request) -> Response {
+        return self(request);
+    }
+}
@@ -0,0 +1,240 @@
+use std::io;
+
+use std::collections::BTreeMap;
+
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+use std::time::Duration;
+
+use crate::http::Error;
+use crate::http::Method;
+use crate::http::Status;
+
+pub(crate) const CONTENT_TYPE_METRICS: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+// Upper bounds of the latency histogram buckets, in seconds.
+const LATENCY_BUCKETS: [f64; 11usize] = [0.005f64, 0.01f64, 0.025f64, 0.05f64, 0.1f64, 0.25f64, 0.5f64, 1f64, 2.5f64, 5f64, 10f64];
+
+// Counters of what `Server` handled since it was created, shared with the threads reading them.
+pub struct Metrics {
+    // By method and status class, such as ("GET", "2xx").
+    requests: Mutex<BTreeMap<(String, String), u64>>,
+
+    // Cumulative counts per bucket, then the total count and sum in microseconds.
+    latency_buckets: [AtomicU64; LATENCY_BUCKETS.len()],
+    latency_count: AtomicU64,
+    latency_sum: AtomicU64,
+
+    connections_in_flight: AtomicUsize,
+
+    bytes_received: AtomicU64,
+    bytes_sent: AtomicU64,
+
+    // By `Error` variant.
+    parse_failures: Mutex<BTreeMap<&'static str, u64>>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        return Self {
+            requests: Mutex::new(BTreeMap::new()),
+
+            latency_buckets: Default::default(),
+            latency_count: AtomicU64::new(0u64),
+            latency_sum: AtomicU64::new(0u64),
+
+            connections_in_flight: AtomicUsize::new(0usize),
+
+            bytes_received: AtomicU64::new(0u64),
+            bytes_sent: AtomicU64::new(0u64),
+
+            parse_failures: Mutex::new(BTreeMap::new()),
+        };
+    }
+
+    pub(crate) fn add_request(&self, method: &Method, status: &Status, latency: Duration) {
+        let class = format!("{}xx", status.get_code() / 100usize);
+
+        *self.requests.lock().unwrap_or_else(|error| error.into_inner()).entry((method.to_string(), class)).or_insert(0u64) += 1u64;
+
+        let seconds = latency.as_secs_f64();
+
+        for (bucket, bound) in self.latency_buckets.iter().zip(LATENCY_BUCKETS.iter()) {
+            if seconds <= *bound {
+                bucket.fetch_add(1u64, Ordering::Relaxed);
+            }
+        }
+
+        self.latency_count.fetch_add(1u64, Ordering::Relaxed);
+        self.latency_sum.fetch_add(latency.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn add_parse_failure(&self, error: &Error) {
+        *self.parse_failures.lock().unwrap_or_else(|error| error.into_inner()).entry(error.get_name()).or_insert(0u64) += 1u64;
+    }
+
+    pub(crate) fn add_bytes_received(&self, bytes: usize) {
+        self.bytes_received.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn add_bytes_sent(&self, bytes: usize) {
+        self.bytes_sent.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub fn get_requests(&self, method: &Method, status_class: &str) -> u64 {
+        let requests = self.requests.lock().unwrap_or_else(|error| error.into_inner());
+
+        return requests.get(&(method.to_string(), String::from(status_class))).copied().unwrap_or(0u64);
+    }
+
+    pub fn get_connections_in_flight(&self) -> usize {
+        return self.connections_in_flight.load(Ordering::Relaxed);
+    }
+
+    pub fn get_bytes_received(&self) -> u64 {
+        return self.bytes_received.load(Ordering::Relaxed);
+    }
+
+    pub fn get_bytes_sent(&self) -> u64 {
+        return self.bytes_sent.load(Ordering::Relaxed);
+    }
+
+    pub fn get_parse_failures(&self, error: &Error) -> u64 {
+        return self.parse_failures.lock().unwrap_or_else(|error| error.into_inner()).get(error.get_name()).copied().unwrap_or(0u64);
+    }
+
+    // All metrics in the Prometheus text exposition format (version 0.0.4).
+    pub fn render(&self) -> String {
+        let mut output = String::new();
+
+        output.push_str("# HELP bwf_http_requests_total Requests answered, by method and status class.\n");
+        output.push_str("# TYPE bwf_http_requests_total counter\n");
+
+        for ((method, class), count) in self.requests.lock().unwrap_or_else(|error| error.into_inner()).iter() {
+            output.push_str(&format!("bwf_http_requests_total{{method=\"{}\",status=\"{}\"}} {}\n", method, class, count));
+        }
+
+        output.push_str("# HELP bwf_http_request_duration_seconds Time from accepting the connection to writing the response.\n");
+        output.push_str("# TYPE bwf_http_request_duration_seconds histogram\n");
+
+        let count = self.latency_count.load(Ordering::Relaxed);
+
+        for (bucket, bound) in self.latency_buckets.iter().zip(LATENCY_BUCKETS.iter()) {
+            output.push_str(&format!("bwf_http_request_duration_seconds_bucket{{le=\"{}\"}} {}\n", bound, bucket.load(Ordering::Relaxed)));
+        }
+
+        output.push_str(&format!("bwf_http_request_duration_seconds_bucket{{le=\"+Inf\"}} {}\n", count));
+        output.push_str(&format!("bwf_http_request_duration_seconds_sum {}\n", self.latency_sum.load(Ordering::Relaxed) as f64 / 1e6f64));
+        output.push_str(&format!("bwf_http_request_duration_seconds_count {}\n", count));
+
+        output.push_str("# HELP bwf_http_connections_in_flight Connections being handled.\n");
+        output.push_str("# TYPE bwf_http_connections_in_flight gauge\n");
+        output.push_str(&format!("bwf_http_connections_in_flight {}\n", self.get_connections_in_flight()));
+
+        output.push_str("# HELP bwf_http_received_bytes_total Bytes read from connections.\n");
+        output.push_str("# TYPE bwf_http_received_bytes_total counter\n");
+        output.push_str(&format!("bwf_http_received_bytes_total {}\n", self.get_bytes_received()));
+
+        output.push_str("# HELP bwf_http_sent_bytes_total Bytes written to connections.\n");
+        output.push_str("# TYPE bwf_http_sent_bytes_total counter\n");
+        output.push_str(&format!("bwf_http_sent_bytes_total {}\n", self.get_bytes_sent()));
+
+        output.push_str("# HELP bwf_http_parse_failures_total Requests that could not be parsed, by error.\n");
+        output.push_str("# TYPE bwf_http_parse_failures_total counter\n");
+
+        for (error, count) in self.parse_failures.lock().unwrap_or_else(|error| error.into_inner()).iter() {
+            output.push_str(&format!("bwf_http_parse_failures_total{{error=\"{}\"}} {}\n", error, count));
+        }
+
+        return output;
+    }
+}
+
+// Counts a connection as in flight until dropped.
+pub(crate) struct InFlight {
+    metrics: Arc<Metrics>,
+}
+
+impl InFlight {
+    pub(crate) fn enter(metrics: &Arc<Metrics>) -> Self {
+        metrics.connections_in_flight.fetch_add(1usize, Ordering::Relaxed);
+
+        return Self {
+            metrics: metrics.clone(),
+        };
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.metrics.connections_in_flight.fetch_sub(1usize, Ordering::Relaxed);
+    }
+}
+
+// Reader counting the bytes received into the metrics.
+pub(crate) struct CountingReader<R> {
+    reader: R,
+
+    metrics: Arc<Metrics>,
+}
+
+impl<R> CountingReader<R> {
+    pub(crate) fn new(reader: R, metrics: &Arc<Metrics>) -> Self {
+        return Self {
+            reader,
+
+            metrics: metrics.clone(),
+        };
+    }
+}
+
+impl<R: io::Read> io::Read for CountingReader<R> {
+    fn read(&mut self, output: &mut [u8]) -> io::Result<usize> {
+        let length = self.reader.read(output)?;
+
+        self.metrics.add_bytes_received(length);
+
+        return Ok(length);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod assertions {
+        use std::time::Duration;
+
+        use crate::http::Error;
+        use crate::http::Method;
+        use crate::http::Status;
+
+        use super::super::Metrics;
+
+        #[test]
+        fn render() {
+            let metrics = Metrics::new();
+
+            metrics.add_request(&Method::GET, &Status::OK, Duration::from_millis(20u64));
+            metrics.add_request(&Method::GET, &Status::NotFound, Duration::from_millis(300u64));
+            metrics.add_request(&Method::GET, &Status::OK, Duration::from_secs(20u64));
+            metrics.add_parse_failure(&Error::URITooLong);
+            metrics.add_bytes_sent(120usize);
+
+            assert_eq!(metrics.get_requests(&Method::GET, "2xx"), 2u64);
+
+            let output = metrics.render();
+
+            assert!(output.contains("bwf_http_requests_total{method=\"GET\",status=\"2xx\"} 2\n"));
+            assert!(output.contains("bwf_http_requests_total{method=\"GET\",status=\"4xx\"} 1\n"));
+            assert!(output.contains("bwf_http_request_duration_seconds_bucket{le=\"0.025\"} 1\n"));
+            assert!(output.contains("bwf_http_request_duration_seconds_bucket{le=\"0.5\"} 2\n"));
+            assert!(output.contains("bwf_http_request_duration_seconds_bucket{le=\"+Inf\"} 3\n"));
+            assert!(output.contains("bwf_http_request_duration_seconds_sum 20.32\n"));
+            assert!(output.contains("bwf_http_sent_bytes_total 120\n"));
+            assert!(output.contains("bwf_http_parse_failures_total{error=\"URITooLong\"} 1\n"));
+        }
+    }
+}
@@ -0,0 +1,256 @@
+use super::Handler;
+use super::Request;
+use super::Response;
+
+// Wraps the handling of a request: it may change the request before passing it on with `next`,
+// answer by itself without calling `next`, or change the response `next` returned.
+pub trait Middleware {
+    fn handle(&self, request: &mut Request, next: &Next) -> Response;
+}
+
+impl<F> Middleware for F where F: Fn(&mut Request, &Next) -> Response {
+    fn handle(&self, request: &mut Request, next: &Next) -> Response {
+        return self(request, next);
+    }
+}
+
+// Runs before the rest of the pipeline, which is skipped when it returns a response.
+pub struct Before<F> {
+    hook: F,
+}
+
+pub fn before<F>(hook: F) -> Before<F> where F: Fn(&mut Request) -> Option<Response> {
+    return Before {
+        hook,
+    };
+}
+
+impl<F> Middleware for Before<F> where F: Fn(&mut Request) -> Option<Response> {
+    fn handle(&self, request: &mut Request, next: &Next) -> Response {
+        return match (self.hook)(request) {
+            Some(response) => response,
+            None => next.run(request),
+        };
+    }
+}
+
+// Runs on the response once the rest of the pipeline returned it.
+pub struct After<F> {
+    hook: F,
+}
+
+pub fn after<F>(hook: F) -> After<F> where F: Fn(&Request, &mut Response) {
+    return After {
+        hook,
+    };
+}
+
+impl<F> Middleware for After<F> where F: Fn(&Request, &mut Response) {
+    fn handle(&self, request: &mut Request, next: &Next) -> Response {
+        let mut response = next.run(request);
+
+        (self.hook)(request, &mut response);
+
+        return response;
+    }
+}
+
+struct Layer {
+    // Path prefix the middleware applies to, every request when None.
+    route: Option<String>,
+
+    middleware: Box<dyn Middleware>,
+}
+
+impl Layer {
+    fn is_matching(&self, path: &str) -> bool {
+        let route = match &self.route {
+            Some(route) => route.trim_end_matches('/'),
+            None => return true,
+        };
+
+        // "/api" covers "/api" and "/api/users", not "/apis".
+        return match path.strip_prefix(route) {
+            Some(rest) => rest.is_empty() || rest.starts_with('/'),
+            None => false,
+        };
+    }
+}
+
+// Rest of the pipeline after a middleware: the following middlewares, then the handler.
+pub struct Next<'a> {
+    layers: &'a [Layer],
+
+    handler: &'a dyn Handler,
+}
+
+impl Next<'_> {
+    pub fn run(&self, request: &mut Request) -> Response {
+        let mut layers = self.layers;
+
+        // Middlewares attached to other routes are skipped.
+        while let Some((layer, rest)) = layers.split_first() {
+            if layer.is_matching(request.get_path()) {
+                let next = Next {
+                    layers: rest,
+
+                    handler: self.handler,
+                };
+
+                return layer.middleware.handle(request, &next);
+            }
+
+            layers = rest;
+        }
+
+        return self.handler.handle(request);
+    }
+}
+
+// Handler wrapped in middlewares. They see the request in the order they were added, and the response in reverse order,
+// so the first one added is the outermost.
+pub struct Pipeline {
+    layers: Vec<Layer>,
+
+    handler: Box<dyn Handler>,
+}
+
+impl Pipeline {
+    pub fn new<H: Handler + 'static>(handler: H) -> Self {
+        return Self {
+            layers: Vec::new(),
+
+            handler: Box::new(handler),
+        };
+    }
+
+    pub fn set_handler<H: Handler + 'static>(&mut self, handler: H) {
+        self.handler = Box::new(handler);
+    }
+
+    pub fn add<M: Middleware + 'static>(&mut self, middleware: M) {
+        self.layers.push(Layer {
+            route: None,
+
+            middleware: Box::new(middleware),
+        });
+    }
+
+    // Adds a middleware only applied to requests whose path is `route` or below it.
+    pub fn add_route<M: Middleware + 'static>(&mut self, route: &str, middleware: M) {
+        self.layers.push(Layer {
+            route: Some(String::from(route)),
+
+            middleware: Box::new(middleware),
+        });
+    }
+
+    pub fn with<M: Middleware + 'static>(mut self, middleware: M) -> Self {
+        self.add(middleware);
+
+        return self;
+    }
+
+    pub fn with_route<M: Middleware + 'static>(mut self, route: &str, middleware: M) -> Self {
+        self.add_route(route, middleware);
+
+        return self;
+    }
+
+    pub fn len(&self) -> usize {
+        return self.layers.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.layers.is_empty();
+    }
+
+    // Runs the middlewares around `handler` instead of the pipeline's own, for responses the server makes itself.
+    pub(crate) fn handle_with(&self, request: &mut Request, handler: &dyn Handler) -> Response {
+        let next = Next {
+            layers: &self.layers,
+
+            handler,
+        };
+
+        return next.run(request);
+    }
+}
+
+impl Handler for Pipeline {
+    fn handle(&self, request: &mut Request) -> Response {
+        return self.handle_with(request, self.handler.as_ref());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod assertions {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        use crate::http::Status;
+
+        use super::super::Request;
+        use super::super::Response;
+        use super::super::Next;
+        use super::super::Pipeline;
+        use super::super::before;
+        use super::super::after;
+
+        fn handle(pipeline: &Pipeline, target: &str) -> Response {
+            let mut request = Request::parse(&mut format!("GET {} HTTP/1.1\r\n\r\n", target).as_bytes()).ok().unwrap();
+
+            return crate::http::server::Handler::handle(pipeline, &mut request);
+        }
+
+        #[test]
+        fn order() {
+            let trace = Rc::new(RefCell::new(Vec::new()));
+
+            let (first, second, handler) = (trace.clone(), trace.clone(), trace.clone());
+
+            let pipeline = Pipeline::new(move |_: &mut Request| {
+                    handler.borrow_mut().push("handler");
+
+                    return Response::new();
+                })
+                .with(move |request: &mut Request, next: &Next| {
+                    first.borrow_mut().push("first before");
+                    let response = next.run(request);
+                    first.borrow_mut().push("first after");
+
+                    return response;
+                })
+                .with(after(move |_: &Request, response: &mut Response| {
+                    second.borrow_mut().push("second after");
+                    response.set_header("X-Second", "1");
+                }));
+
+            let response = handle(&pipeline, "/");
+
+            assert_eq!(*trace.borrow(), vec!["first before", "handler", "second after", "first after"]);
+            assert_eq!(response.get_headers().get("X-Second"), Some("1"));
+        }
+
+        #[test]
+        fn route() {
+            let pipeline = Pipeline::new(|_: &mut Request| Response::new())
+                .with_route("/admin/", before(|request: &mut Request| {
+                    if request.get_headers().contains("Authorization") {
+                        return None;
+                    }
+
+                    let mut response = Response::new();
+                    response.set_status(Status::Unauthorized);
+
+                    return Some(response);
+                }));
+
+            assert_eq!(*handle(&pipeline, "/admin").get_status(), Status::Unauthorized);
+            assert_eq!(*handle(&pipeline, "/admin/users?page=2").get_status(), Status::Unauthorized);
+            assert_eq!(*handle(&pipeline, "/administrator").get_status(), Status::OK);
+            assert_eq!(*handle(&pipeline, "/").get_status(), Status::OK);
+        }
+    }
+}
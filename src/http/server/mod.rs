@@ -1,12 +1,47 @@
 mod server;
+mod handler;
+mod middleware;
+mod access_log;
+mod metrics;
+mod panic;
+mod error_page;
+mod problem;
+mod files;
+mod range;
 
 mod request;
 mod response;
+mod body;
 
 pub use self::server::Server;
+pub use self::handler::Handler;
+pub use self::middleware::Middleware;
+pub use self::middleware::Next;
+pub use self::middleware::Pipeline;
+pub use self::middleware::Before;
+pub use self::middleware::After;
+pub use self::middleware::before;
+pub use self::middleware::after;
+pub use self::access_log::AccessEntry;
+pub use self::access_log::AccessLogger;
+pub use self::access_log::AccessLog;
+pub use self::access_log::LogFormat;
+pub use self::access_log::RotatingFile;
+pub use self::metrics::Metrics;
+pub use self::error_page::ErrorRenderer;
+pub use self::error_page::render_html;
+pub use self::error_page::render_problem;
+pub use self::problem::Problem;
+pub use self::problem::TryHandler;
+pub use self::problem::try_handler;
+pub use self::files::StaticFiles;
+pub use self::files::get_content_type;
 
 pub use self::request::Request;
+pub use super::codec::RequestRef;
+pub use super::codec::ParseMode;
 pub use self::response::Response;
+pub use self::body::Body;
 
 pub use super::Error;
 pub use super::Result;
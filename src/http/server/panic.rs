@@ -0,0 +1,117 @@
+use std::any::Any;
+use std::cell::Cell;
+use std::cell::RefCell;
+
+use std::backtrace::Backtrace;
+
+use std::panic;
+use std::panic::AssertUnwindSafe;
+
+use std::sync::Once;
+
+static HOOK: Once = Once::new();
+
+thread_local! {
+    // Set while running code whose panics are caught here.
+    static CATCHING: Cell<bool> = const { Cell::new(false) };
+
+    // Where the last caught panic happened, and how it got there.
+    static CAUGHT: RefCell<Option<(String, Backtrace)>> = const { RefCell::new(None) };
+}
+
+// A panic caught while handling a request.
+pub(crate) struct Panic {
+    message: String,
+
+    location: String,
+
+    backtrace: Option<Backtrace>,
+}
+
+impl Panic {
+    pub(crate) fn get_message(&self) -> &str {
+        return &self.message;
+    }
+
+    pub(crate) fn get_location(&self) -> &str {
+        return &self.location;
+    }
+
+    pub(crate) fn get_backtrace(&self) -> Option<&Backtrace> {
+        return self.backtrace.as_ref();
+    }
+}
+
+fn get_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        return String::from(*message);
+    }
+
+    if let Some(message) = payload.downcast_ref::<String>() {
+        return message.clone();
+    }
+
+    return String::from("Box<dyn Any>");
+}
+
+// Replaces the panic hook, once, by one that records where caught panics happened instead of printing them.
+// Other panics still go to the hook that was in place.
+fn install_hook() {
+    HOOK.call_once(|| {
+        let previous = panic::take_hook();
+
+        panic::set_hook(Box::new(move |info| {
+            if !CATCHING.with(|catching| catching.get()) {
+                previous(info);
+
+                return;
+            }
+
+            let location = info.location().map(|location| location.to_string()).unwrap_or_default();
+
+            CAUGHT.with(|caught| *caught.borrow_mut() = Some((location, Backtrace::force_capture())));
+        }));
+    });
+}
+
+// Runs `function`, turning a panic into an error instead of unwinding further.
+pub(crate) fn catch<F: FnOnce() -> R, R>(function: F) -> Result<R, Panic> {
+    install_hook();
+
+    let catching = CATCHING.with(|catching| catching.replace(true));
+    let result = panic::catch_unwind(AssertUnwindSafe(function));
+    CATCHING.with(|flag| flag.set(catching));
+
+    return result.map_err(|payload| {
+        let (location, backtrace) = match CAUGHT.with(|caught| caught.borrow_mut().take()) {
+            Some((location, backtrace)) => (location, Some(backtrace)),
+            None => (String::new(), None),
+        };
+
+        return Panic {
+            message: get_message(payload.as_ref()),
+
+            location,
+
+            backtrace,
+        };
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    mod assertions {
+        use super::super::catch;
+
+        #[test]
+        fn catch_panic() {
+            assert_eq!(catch(|| 1u32).ok(), Some(1u32));
+
+            let panic = catch(|| -> u32 { panic!("handler failed: {}", 42u32) }).err().unwrap();
+
+            assert_eq!(panic.get_message(), "handler failed: 42");
+            assert!(panic.get_location().contains("panic.rs"));
+            assert!(panic.get_backtrace().is_some());
+        }
+    }
+}
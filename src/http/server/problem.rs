@@ -0,0 +1,230 @@
+use std::fmt;
+
+use crate::http::Error;
+use crate::http::Status;
+
+use crate::http::json;
+
+use super::Handler;
+use super::Request;
+use super::Response;
+
+pub(crate) const CONTENT_TYPE_PROBLEM: &str = "application/problem+json";
+
+const TYPE_BLANK: &str = "about:blank";
+
+// Problem details for an HTTP API (RFC 7807), sent as "application/problem+json".
+// Handlers can return it as an error, see `try_handler`.
+#[derive(PartialEq, Eq, Clone)]
+pub struct Problem {
+    // URI reference identifying the problem type, "about:blank" when the status says it all.
+    problem_type: String,
+
+    // Defaults to the reason phrase of the status.
+    title: Option<String>,
+
+    status: Status,
+
+    detail: Option<String>,
+
+    instance: Option<String>,
+
+    // Extension members, by name, as serialized JSON values.
+    extensions: Vec<(String, String)>,
+}
+
+impl Problem {
+    pub fn new(status: Status) -> Self {
+        return Self {
+            problem_type: String::from(TYPE_BLANK),
+
+            title: None,
+
+            status,
+
+            detail: None,
+
+            instance: None,
+
+            extensions: Vec::new(),
+        };
+    }
+
+    pub fn set_type(&mut self, problem_type: &str) {
+        self.problem_type = String::from(problem_type);
+    }
+
+    pub fn get_type(&self) -> &str {
+        return &self.problem_type;
+    }
+
+    pub fn set_title(&mut self, title: &str) {
+        self.title = Some(String::from(title));
+    }
+
+    pub fn get_title(&self) -> String {
+        return match &self.title {
+            Some(title) => title.clone(),
+            None => String::from_utf8_lossy(self.status.get_text()).into_owned(),
+        };
+    }
+
+    pub fn set_status(&mut self, status: Status) {
+        self.status = status;
+    }
+
+    pub fn get_status(&self) -> &Status {
+        return &self.status;
+    }
+
+    pub fn set_detail(&mut self, detail: &str) {
+        self.detail = Some(String::from(detail));
+    }
+
+    pub fn get_detail(&self) -> Option<&str> {
+        return self.detail.as_deref();
+    }
+
+    pub fn set_instance(&mut self, instance: &str) {
+        self.instance = Some(String::from(instance));
+    }
+
+    pub fn get_instance(&self) -> Option<&str> {
+        return self.instance.as_deref();
+    }
+
+    // Adds a member with a string value, replacing one with the same name.
+    pub fn set_extension(&mut self, name: &str, value: &str) {
+        self.set_extension_json(name, &json::escape(value));
+    }
+
+    // Same with a value that is already JSON, such as "42", "true", or "[\"a\", \"b\"]", which is not checked.
+    pub fn set_extension_json(&mut self, name: &str, value: &str) {
+        self.extensions.retain(|(extension, _)| extension != name);
+        self.extensions.push((String::from(name), String::from(value)));
+    }
+
+    pub fn get_extension_json(&self, name: &str) -> Option<&str> {
+        return self.extensions.iter().find(|(extension, _)| extension == name).map(|(_, value)| value.as_str());
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut output = format!("{{\"type\":{},\"title\":{},\"status\":{}",
+            json::escape(&self.problem_type), json::escape(&self.get_title()), self.status.get_code());
+
+        if let Some(detail) = &self.detail {
+            output.push_str(&format!(",\"detail\":{}", json::escape(detail)));
+        }
+
+        if let Some(instance) = &self.instance {
+            output.push_str(&format!(",\"instance\":{}", json::escape(instance)));
+        }
+
+        // The standard members win over extensions that would shadow them.
+        for (name, value) in self.extensions.iter() {
+            if !["type", "title", "status", "detail", "instance"].contains(&name.as_str()) {
+                output.push_str(&format!(",{}:{}", json::escape(name), value));
+            }
+        }
+
+        output.push('}');
+
+        return output;
+    }
+}
+
+impl From<Problem> for Response {
+    fn from(problem: Problem) -> Self {
+        let mut response = Response::new();
+
+        response.set_status(problem.status);
+        response.set_header("Content-Type", CONTENT_TYPE_PROBLEM);
+        response.set_body(problem.to_json());
+
+        return response;
+    }
+}
+
+// So that `?` can be used on the crate's results in handlers returning problems.
+impl From<Error> for Problem {
+    fn from(error: Error) -> Self {
+        let mut problem = Problem::new(error.get_status());
+
+        // Internal failures are not detailed to clients.
+        if !matches!(error, Error::InternalError(_)) {
+            problem.set_detail(&error.to_string());
+        }
+
+        return problem;
+    }
+}
+
+impl fmt::Debug for Problem {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return formatter.write_str(&self.to_json());
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match &self.detail {
+            Some(detail) => write!(formatter, "{}: {}", self.get_title(), detail),
+            None => formatter.write_str(&self.get_title()),
+        };
+    }
+}
+
+impl std::error::Error for Problem {}
+
+// Handler returning a result, whose error becomes the response.
+pub struct TryHandler<F> {
+    handler: F,
+}
+
+// Wraps a handler such as `|request| -> Result<Response, Problem>`.
+pub fn try_handler<F, E>(handler: F) -> TryHandler<F> where F: Fn(&mut Request) -> Result<Response, E>, E: Into<Response> {
+    return TryHandler {
+        handler,
+    };
+}
+
+impl<F, E> Handler for TryHandler<F> where F: Fn(&mut Request) -> Result<Response, E>, E: Into<Response> {
+    fn handle(&self, request: &mut Request) -> Response {
+        return match (self.handler)(request) {
+            Ok(response) => response,
+            Err(error) => error.into(),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod assertions {
+        use crate::http::Error;
+        use crate::http::Status;
+
+        use super::super::Problem;
+        use super::super::Response;
+
+        #[test]
+        fn to_json() {
+            let mut problem = Problem::new(Status::Forbidden);
+            problem.set_type("https://example.com/probs/out-of-credit");
+            problem.set_title("You do not have enough credit.");
+            problem.set_detail("Your current balance is 30, but that costs 50.");
+            problem.set_instance("/account/12345/msgs/abc");
+            problem.set_extension_json("balance", "30");
+            problem.set_extension("currency", "EUR");
+            problem.set_extension_json("status", "200");
+
+            assert_eq!(problem.to_json(), "{\"type\":\"https://example.com/probs/out-of-credit\",\"title\":\"You do not have enough credit.\",\"status\":403,\
+                \"detail\":\"Your current balance is 30, but that costs 50.\",\"instance\":\"/account/12345/msgs/abc\",\"balance\":30,\"currency\":\"EUR\"}");
+
+            let response = Response::from(Problem::from(Error::URITooLong));
+
+            assert_eq!(*response.get_status(), Status::URITooLong);
+            assert_eq!(response.get_headers().get("Content-Type"), Some("application/problem+json"));
+            assert_eq!(response.get_body(), &b"{\"type\":\"about:blank\",\"title\":\"URI Too Long\",\"status\":414,\"detail\":\"URI Too Long\"}"[..]);
+        }
+    }
+}
@@ -0,0 +1,443 @@
+use std::io;
+use std::io::Read;
+
+use std::collections::VecDeque;
+
+use crate::http::Method;
+use crate::http::Status;
+
+use crate::http::date;
+use crate::http::request_id;
+
+use super::Request;
+use super::Response;
+
+use super::response::Source;
+
+// More ranges than this in one request are ignored, sending the whole body instead.
+const MAX_RANGES: usize = 64usize;
+
+// First and last positions of a range, both included.
+type Range = (u64, u64);
+
+// Ranges of a "Range: bytes=..." header, unresolved: None stands for an open end, or for a suffix length when first.
+// None when the header is malformed or in another unit, which is not an error but makes it ignored (RFC 7233 3.1).
+fn parse_ranges(header: &str) -> Option<Vec<(Option<u64>, Option<u64>)>> {
+    let (unit, ranges) = header.split_once('=')?;
+
+    if !unit.trim().eq_ignore_ascii_case("bytes") {
+        return None;
+    }
+
+    let mut output = Vec::new();
+
+    for range in ranges.split(',').map(str::trim).filter(|range| !range.is_empty()) {
+        let (first, last) = range.split_once('-')?;
+
+        let parse = |position: &str| -> Option<Option<u64>> {
+            let position = position.trim();
+
+            if position.is_empty() {
+                return Some(None);
+            }
+
+            if !position.bytes().all(|byte| byte.is_ascii_digit()) {
+                return None;
+            }
+
+            return position.parse::<u64>().ok().map(Some);
+        };
+
+        let (first, last) = (parse(first)?, parse(last)?);
+
+        match (first, last) {
+            (None, None) => return None,
+            (Some(first), Some(last)) if last < first => return None,
+            _ => output.push((first, last)),
+        }
+    }
+
+    if output.is_empty() {
+        return None;
+    }
+
+    return Some(output);
+}
+
+// Positions of the satisfiable ranges in a body of `length` bytes, in the order asked for.
+// When some overlap or touch they are coalesced instead, and sorted then (RFC 7233 4.1).
+fn resolve_ranges(ranges: &[(Option<u64>, Option<u64>)], length: u64) -> Vec<Range> {
+    let output: Vec<Range> = ranges.iter().filter_map(|range| {
+        return match *range {
+            (Some(first), _) if first >= length => None,
+            (Some(first), last) => Some((first, last.map_or(length - 1u64, |last| last.min(length - 1u64)))),
+            (None, Some(0u64)) => None,
+            (None, Some(suffix)) if length > 0u64 => Some((length.saturating_sub(suffix), length - 1u64)),
+            _ => None,
+        };
+    }).collect();
+
+    let mut sorted = output.clone();
+    sorted.sort_unstable();
+
+    let mut coalesced: Vec<Range> = Vec::with_capacity(sorted.len());
+
+    for (first, last) in sorted {
+        match coalesced.last_mut() {
+            Some(previous) if first <= previous.1 + 1u64 => previous.1 = previous.1.max(last),
+            _ => coalesced.push((first, last)),
+        }
+    }
+
+    if coalesced.len() == output.len() {
+        return output;
+    }
+
+    return coalesced;
+}
+
+// Whether the validator of "If-Range" still matches the response, for which only strong entity tags do.
+fn is_if_range_matching(if_range: &str, response: &Response) -> bool {
+    let if_range = if_range.trim();
+
+    if if_range.starts_with('"') {
+        return response.get_headers().get("ETag").is_some_and(|entity_tag| entity_tag.trim() == if_range);
+    }
+
+    if if_range.starts_with("W/") {
+        return false;
+    }
+
+    let last_modified = response.get_headers().get("Last-Modified").and_then(date::parse_http_date);
+
+    return last_modified.is_some() && last_modified == date::parse_http_date(if_range);
+}
+
+enum Part {
+    Bytes(Vec<u8>),
+    // Start and length of a range of the source.
+    Range(u64, u64),
+}
+
+// Reads ranges of a body, in order, with the bytes in between them such as the headers of multipart parts.
+struct RangeReader {
+    source: Source,
+    position: u64,
+
+    parts: VecDeque<Part>,
+}
+
+impl io::Read for RangeReader {
+    fn read(&mut self, output: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let part = match self.parts.front_mut() {
+                Some(part) => part,
+                None => return Ok(0usize),
+            };
+
+            match part {
+                Part::Bytes(bytes) if !bytes.is_empty() => {
+                    let length = bytes.len().min(output.len());
+
+                    output[..length].copy_from_slice(&bytes[..length]);
+                    bytes.drain(..length);
+
+                    return Ok(length);
+                }
+                Part::Range(start, remaining) if *remaining > 0u64 => {
+                    if self.position != *start {
+                        self.source.skip(self.position, *start)?;
+                        self.position = *start;
+                    }
+
+                    let limit = (*remaining).min(output.len() as u64) as usize;
+                    let length = self.source.read(&mut output[..limit])?;
+
+                    if length == 0usize {
+                        return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+                    }
+
+                    self.position += length as u64;
+                    *start += length as u64;
+                    *remaining -= length as u64;
+
+                    return Ok(length);
+                }
+                _ => {
+                    self.parts.pop_front();
+                }
+            }
+        }
+    }
+}
+
+// Answers "Range" requests (RFC 7233) from a complete response whose length is known: "206 Partial Content"
+// with one range, or several as "multipart/byteranges", and "416 Range Not Satisfiable" when none is.
+// Handlers sending "Accept-Ranges: none", or ranges themselves, are left alone.
+pub(crate) fn apply(request: &Request, response: &mut Response) {
+    let method = *request.get_method();
+
+    if (method != Method::GET && method != Method::HEAD) || *response.get_status() != Status::OK {
+        return;
+    }
+
+    let headers = response.get_headers();
+
+    if headers.get("Accept-Ranges").is_some_and(|unit| !unit.eq_ignore_ascii_case("bytes")) {
+        return;
+    }
+
+    if headers.contains("Content-Range") || headers.contains("Transfer-Encoding") {
+        return;
+    }
+
+    let length = match response.get_content_length() {
+        Some(length) => length as u64,
+        None => return,
+    };
+
+    response.set_header("Accept-Ranges", "bytes");
+
+    if method != Method::GET {
+        return;
+    }
+
+    let ranges = match request.get_headers().get("Range").and_then(parse_ranges) {
+        Some(ranges) if ranges.len() <= MAX_RANGES => ranges,
+        _ => return,
+    };
+
+    if let Some(if_range) = request.get_headers().get("If-Range") {
+        if !is_if_range_matching(if_range, response) {
+            return;
+        }
+    }
+
+    let mut ranges = resolve_ranges(&ranges, length);
+
+    response.remove_header("Content-Length");
+
+    if ranges.is_empty() {
+        response.set_status(Status::RangeNotSatisfiable);
+        response.set_header("Content-Range", &format!("bytes */{}", length));
+        response.remove_header("Content-Type");
+        response.set_body(Vec::new());
+
+        return;
+    }
+
+    let streaming = response.is_streaming();
+    let source = response.take_source();
+
+    // A stream is only read forward, so its ranges are sent in the order they come in the body.
+    if !source.is_seekable() {
+        ranges.sort_unstable();
+    }
+
+    let mut parts = VecDeque::new();
+    let mut content_length = 0u64;
+
+    if let [(first, last)] = ranges[..] {
+        response.set_header("Content-Range", &format!("bytes {}-{}/{}", first, last, length));
+
+        parts.push_back(Part::Range(first, last - first + 1u64));
+        content_length = last - first + 1u64;
+    } else {
+        let boundary = request_id::generate().replace('-', "");
+        let content_type = response.get_headers().get("Content-Type").map(String::from);
+
+        for (first, last) in ranges {
+            let mut head = format!("\r\n--{}\r\n", boundary);
+
+            if let Some(content_type) = &content_type {
+                head.push_str(&format!("Content-Type: {}\r\n", content_type));
+            }
+
+            head.push_str(&format!("Content-Range: bytes {}-{}/{}\r\n\r\n", first, last, length));
+
+            content_length += head.len() as u64 + last - first + 1u64;
+
+            parts.push_back(Part::Bytes(head.into_bytes()));
+            parts.push_back(Part::Range(first, last - first + 1u64));
+        }
+
+        let tail = format!("\r\n--{}--\r\n", boundary);
+        content_length += tail.len() as u64;
+
+        parts.push_back(Part::Bytes(tail.into_bytes()));
+
+        response.set_header("Content-Type", &format!("multipart/byteranges; boundary={}", boundary));
+    }
+
+    response.set_status(Status::PartialContent);
+
+    let mut reader = RangeReader {
+        source,
+        position: 0u64,
+
+        parts,
+    };
+
+    if streaming {
+        response.set_reader(Box::new(reader), Some(content_length as usize));
+    } else {
+        let mut body = Vec::with_capacity(content_length as usize);
+
+        // Reading ranges of a body held in memory cannot fail.
+        let _ = reader.read_to_end(&mut body);
+
+        response.set_body(body);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod assertions {
+        use std::io;
+
+        use crate::http::Status;
+
+        use super::super::Request;
+        use super::super::Response;
+        use super::super::Source;
+        use super::super::apply;
+
+        fn get_response(range: &str) -> Response {
+            return get_response_with(&format!("Range: {}\r\n", range), &[]);
+        }
+
+        // Response to a request with `fields`, from a handler giving the body "0123456789" with `headers`.
+        fn get_response_with(fields: &str, headers: &[(&str, &str)]) -> Response {
+            let head = format!("GET /video.mp4 HTTP/1.1\r\n{}\r\n", fields);
+            let request = Request::parse(&mut head.as_bytes()).ok().unwrap();
+
+            let mut response = Response::new();
+            response.set_header("Content-Type", "text/plain");
+            response.set_body("0123456789");
+
+            for (name, value) in headers {
+                response.set_header(name, value);
+            }
+
+            apply(&request, &mut response);
+
+            return response;
+        }
+
+        #[test]
+        fn ranges() {
+            let response = get_response("bytes=2-4");
+
+            assert_eq!(*response.get_status(), Status::PartialContent);
+            assert_eq!(response.get_headers().get("Content-Range"), Some("bytes 2-4/10"));
+            assert_eq!(response.get_body(), b"234");
+
+            assert_eq!(get_response("bytes=-3").get_body(), b"789");
+            assert_eq!(get_response("bytes=7-").get_body(), b"789");
+            let response = get_response("bytes=8-20, 0-0, 1-2");
+            let body = String::from_utf8_lossy(response.get_body()).into_owned();
+
+            assert!(response.get_headers().get("Content-Type").unwrap().starts_with("multipart/byteranges; boundary="));
+            assert!(body.contains("\r\nContent-Type: text/plain\r\nContent-Range: bytes 0-2/10\r\n\r\n012\r\n--"));
+            assert!(body.contains("\r\nContent-Range: bytes 8-9/10\r\n\r\n89\r\n--"));
+            assert_eq!(response.get_content_length(), Some(body.len()));
+            assert_eq!(get_response("bytes=1-2, 3-5").get_headers().get("Content-Range"), Some("bytes 1-5/10"));
+
+            let response = get_response("bytes=10-, -0");
+
+            assert_eq!(*response.get_status(), Status::RangeNotSatisfiable);
+            assert_eq!(response.get_headers().get("Content-Range"), Some("bytes */10"));
+
+            let response = get_response("bytes=4-2");
+
+            assert_eq!(*response.get_status(), Status::OK);
+            assert_eq!(response.get_headers().get("Accept-Ranges"), Some("bytes"));
+            assert_eq!(get_response("items=0-1").get_body(), b"0123456789");
+        }
+
+        #[test]
+        fn multipart() {
+            let response = get_response("bytes=8-9, 0-1");
+
+            let content_type = response.get_headers().get("Content-Type").unwrap();
+            let boundary = content_type.strip_prefix("multipart/byteranges; boundary=").unwrap();
+
+            // Parts come in the order asked for unless some had to be coalesced.
+            let expected = format!(concat!(
+                "\r\n--{0}\r\nContent-Type: text/plain\r\nContent-Range: bytes 8-9/10\r\n\r\n89",
+                "\r\n--{0}\r\nContent-Type: text/plain\r\nContent-Range: bytes 0-1/10\r\n\r\n01",
+                "\r\n--{0}--\r\n",
+            ), boundary);
+
+            assert_eq!(*response.get_status(), Status::PartialContent);
+            assert_eq!(response.get_headers().get("Content-Range"), None);
+            assert_eq!(String::from_utf8_lossy(response.get_body()), expected);
+
+            let body = String::from_utf8_lossy(get_response("bytes=6-7, 0-1, 1-3").get_body()).into_owned();
+
+            assert!(body.find("bytes 0-3/10").unwrap() < body.find("bytes 6-7/10").unwrap());
+        }
+
+        #[test]
+        fn multipart_stream() {
+            let request = Request::parse(&mut "GET /video.mp4 HTTP/1.1\r\nRange: bytes=8-9, 0-1\r\n\r\n".as_bytes()).ok().unwrap();
+
+            let mut response = Response::new();
+            response.set_reader(Box::new(io::Cursor::new(b"0123456789".to_vec())), Some(10usize));
+
+            apply(&request, &mut response);
+
+            let content_length = response.get_content_length();
+
+            let mut body = Vec::new();
+            response.write_reader(&mut body).ok().unwrap();
+
+            let body = String::from_utf8_lossy(&body).into_owned();
+
+            assert_eq!(*response.get_status(), Status::PartialContent);
+            assert!(body.find("bytes 0-1/10\r\n\r\n01\r\n").unwrap() < body.find("bytes 8-9/10\r\n\r\n89\r\n").unwrap());
+            assert_eq!(content_length, Some(body.len()));
+
+            let mut source = Source::Stream(Box::new(io::Cursor::new(b"0123456789".to_vec())));
+
+            assert!(source.skip(0u64, 5u64).is_ok());
+            assert!(source.skip(5u64, 2u64).is_err());
+        }
+
+        #[test]
+        fn if_range() {
+            let last_modified = "Sun, 06 Nov 1994 08:49:37 GMT";
+            let headers = [("ETag", "\"v1\""), ("Last-Modified", last_modified)];
+
+            let is_partial = |if_range: &str| {
+                let response = get_response_with(&format!("Range: bytes=0-1\r\nIf-Range: {}\r\n", if_range), &headers);
+
+                return *response.get_status() == Status::PartialContent;
+            };
+
+            assert!(is_partial("\"v1\""));
+            assert!(is_partial(last_modified));
+
+            // Any mismatch gets the whole body, and so do weak validators which never match.
+            assert!(!is_partial("\"v2\""));
+            assert!(!is_partial("W/\"v1\""));
+            assert!(!is_partial("Sun, 06 Nov 1994 08:49:38 GMT"));
+
+            let response = get_response_with("Range: bytes=0-1\r\nIf-Range: \"v2\"\r\n", &headers);
+
+            assert_eq!(*response.get_status(), Status::OK);
+            assert_eq!(response.get_body(), b"0123456789");
+        }
+
+        #[test]
+        fn not_satisfiable() {
+            let response = get_response("bytes=20-30");
+
+            assert_eq!(*response.get_status(), Status::RangeNotSatisfiable);
+            assert_eq!(response.get_headers().get("Content-Range"), Some("bytes */10"));
+            assert_eq!(response.get_headers().get("Content-Type"), None);
+            assert!(response.get_body().is_empty());
+            assert_eq!(response.get_content_length(), Some(0usize));
+        }
+    }
+}
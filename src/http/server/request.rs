@@ -4,122 +4,148 @@ use std::io;
 use super::Result;
 use super::Error;
 
-use super::super::{MIN_LENGTH_METHOD, LENGTH_SPACE, MIN_LENGTH_TARGET, LENGTH_PROTOCOL, LENGTH_EOL};
-
 use crate::http::Method;
+use crate::http::Headers;
+use crate::http::Limits;
 
-const MAX_LENGTH_HEADER: usize = 4096usize;
+use crate::http::codec;
+use crate::http::codec::Event;
+use crate::http::codec::ParseMode;
+use crate::http::codec::RequestDecoder;
+use crate::http::codec::RequestRef;
 
-const MIN_LENGTH_REQUEST_LINE_FULL: usize = MIN_LENGTH_METHOD + LENGTH_SPACE + MIN_LENGTH_TARGET + LENGTH_SPACE + LENGTH_PROTOCOL + LENGTH_EOL + LENGTH_EOL;
-// "GET / HTTP/1.1\r\n\r\n"
-const MIN_LENGTH_REQUEST_LINE_SLICED_1: usize = MIN_LENGTH_TARGET + LENGTH_SPACE + LENGTH_PROTOCOL + LENGTH_EOL + LENGTH_EOL;
-// "/ HTTP/1.1\r\n\r\n"
-const MIN_LENGTH_REQUEST_LINE_SLICED_2: usize = LENGTH_PROTOCOL + LENGTH_EOL + LENGTH_EOL;
-// "HTTP/1.1\r\n\r\n"
-const MIN_LENGTH_REQUEST_LINE_SLICED_3: usize = LENGTH_EOL; // "\r\n"
+use super::Body;
 
-pub struct Request {
-    buffer: [u8; MAX_LENGTH_HEADER],
-    buffer_length: usize,
-    buffer_finger: usize,
+const LENGTH_READ: usize = 4096usize;
 
+pub struct Request {
     method: Method,
 
     target: String,
+    trailing_slash: bool,
+
+    headers: Headers,
+
+    content_length: Option<usize>,
+
+    body: Body,
+
+    // Set by `Server` once the request is parsed.
+    id: String,
 }
 
-impl Request {
-    pub fn parse(reader: &mut dyn io::Read) -> Result<Self> {
-        let mut request = Self {
-            buffer: [0u8; MAX_LENGTH_HEADER],
-            buffer_length: 0usize,
-            buffer_finger: 0usize,
+impl<'a> From<RequestRef<'a>> for Request {
+    // The request has no body data attached.
+    fn from(request: RequestRef<'a>) -> Self {
+        return Self {
+            method: *request.get_method(),
 
-            method: Method::GET,
+            target: String::from(request.get_target()),
+            trailing_slash: request.has_trailing_slash(),
 
-            target: String::from("/"),
-        };
+            headers: request.to_headers(),
 
-        request.buffer_length = reader.read(&mut request.buffer)?;
+            content_length: request.get_content_length(),
 
-        if MIN_LENGTH_REQUEST_LINE_FULL > request.buffer_length {
-            return Err(Error::BadRequest);
-        }
+            body: Body::new(request.get_content_length(), request.is_chunked()),
+
+            id: String::new(),
+        };
+    }
+}
 
-        {
-            let space_index = request.buffer_finger + match request.buffer[request.buffer_finger..].iter().position(|&byte| byte == b' ') {
-                Some(index) => index,
-                None => return Err(Error::BadRequest),
-            };
+impl Request {
+    pub fn parse(reader: &mut dyn io::Read) -> Result<Self> {
+        return Self::parse_with(reader, ParseMode::Strict, &Limits::new());
+    }
 
-            request.method = match Method::from_text(&request.buffer[..space_index]) {
-                Some(method) => method,
-                None => return Err(Error::BadRequest),
-            };
+    pub fn parse_with(reader: &mut dyn io::Read, mode: ParseMode, limits: &Limits) -> Result<Self> {
+        let mut decoder = RequestDecoder::new(mode, limits);
 
-            request.buffer_finger = space_index + LENGTH_SPACE;
-        }
+        let mut request = loop {
+            match decoder.poll()? {
+                Some(Event::Head(head)) => break Self::from(head),
+                Some(_) => return Err(Error::BadRequest),
+                None => {
+                    let length = reader.read(decoder.prepare(LENGTH_READ))?;
 
-        if request.buffer_finger + MIN_LENGTH_REQUEST_LINE_SLICED_1 > request.buffer_length {
-            return Err(Error::BadRequest);
-        }
+                    if length == 0usize {
+                        return Err(Error::BadRequest);
+                    }
 
-        {
-            let space_index = request.buffer_finger + match request.buffer[request.buffer_finger..].iter().position(|&byte| byte == b' ') {
-                Some(index) => index,
-                None => return Err(Error::BadRequest),
-            };
-
-            request.target = if request.buffer[request.buffer_finger] == b'/' {
-                if request.buffer_finger + 1usize == space_index {
-                    String::from("/")
-                } else if request.buffer[space_index - 1usize] == b'/' {
-                    String::from(std::str::from_utf8(&request.buffer[request.buffer_finger..space_index - 1usize])?)
-                } else {
-                    String::from(std::str::from_utf8(&request.buffer[request.buffer_finger..space_index])?)
+                    decoder.commit(length);
                 }
-            } else {
-                return Err(Error::BadRequest);
-            };
+            }
+        };
 
-            request.buffer_finger = space_index + LENGTH_SPACE;
-        }
+        // Whatever was read past the head stays in the decoder as the start of the body.
+        request.body.set_decoder(decoder);
 
-        if request.buffer_finger + MIN_LENGTH_REQUEST_LINE_SLICED_2 > request.buffer_length {
-            return Err(Error::BadRequest);
-        }
+        return Ok(request);
+    }
 
-        if !request.buffer[request.buffer_finger..].starts_with(b"HTTP/1.1\r\n") {
-            return Err(Error::BadRequest);
-        }
+    pub fn get_method(&self) -> &Method {
+        return &self.method;
+    }
 
-        request.buffer_finger += LENGTH_PROTOCOL + LENGTH_EOL;
+    pub fn get_target(&self) -> &str {
+        return &self.target;
+    }
 
-        if request.buffer_finger + MIN_LENGTH_REQUEST_LINE_SLICED_3 > request.buffer_length {
-            return Err(Error::BadRequest);
-        }
+    // Whether the target ended with a slash, which `get_target` leaves out, as a request for "/dir/" rather than "/dir".
+    pub fn has_trailing_slash(&self) -> bool {
+        return self.trailing_slash;
+    }
 
-        if !request.buffer[request.buffer_finger..].starts_with(b"\r\n") {
-            return Err(Error::BadRequest);
-        }
+    // The path of the target, without the query, also for targets in absolute form.
+    pub fn get_path(&self) -> &str {
+        let target = match self.target.find("://") {
+            Some(index) => match self.target[index + 3usize..].find('/') {
+                Some(start) => &self.target[index + 3usize + start..],
+                None => "/",
+            },
+            None => self.target.as_str(),
+        };
 
-        request.buffer_finger += LENGTH_EOL;
+        return target.split(&['?', '#'][..]).next().unwrap_or(target);
+    }
 
-        if request.buffer_finger != request.buffer_length {
-            return Err(Error::BadRequest);
-        }
+    pub fn get_headers(&self) -> &Headers {
+        return &self.headers;
+    }
 
-        return Ok(request);
+    pub fn get_content_length(&self) -> Option<usize> {
+        return self.content_length;
     }
 
-    pub fn get_method(&self) -> &Method {
-        return &self.method;
+    pub fn is_expecting_continue(&self) -> bool {
+        return match self.headers.get("Expect") {
+            Some(expect) => expect.eq_ignore_ascii_case("100-continue"),
+            None => false,
+        };
+    }
+
+    // Unique ID of the request, from a trusted "X-Request-Id" header or generated, see `Server::set_request_id_trusted`.
+    pub fn get_id(&self) -> &str {
+        return &self.id;
+    }
+
+    pub(crate) fn set_id(&mut self, id: String) {
+        self.id = id;
+    }
+
+    // Reading from the body sends the pending "100 Continue" if the client asked for one.
+    pub fn get_body(&mut self) -> &mut Body {
+        return &mut self.body;
     }
 }
 
 impl fmt::Display for Request {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        return formatter.write_fmt(format_args!("{} {} HTTP/1.1\r\n\r\n", self.method, self.target));
+        let mut head = Vec::new();
+        codec::encode_request_head(&mut head, &self.method, &self.target, &self.headers);
+
+        return formatter.write_str(&String::from_utf8_lossy(&head));
     }
 }
 
@@ -132,6 +158,8 @@ mod tests {
     const REQUEST_GET_PATH_1: &str = "GET /lorem HTTP/1.1\r\n\r\n";
     const REQUEST_GET_PATH_1_TRAILING: &str = "GET /lorem/ HTTP/1.1\r\n\r\n";
     const REQUEST_GET_PATH_2: &str = "GET /lorem/ipsum HTTP/1.1\r\n\r\n";
+    const REQUEST_POST_LENGTH: &str = "POST /lorem HTTP/1.1\r\nHost: localhost\r\nContent-Length: 5\r\nExpect: 100-continue\r\n\r\nipsum";
+    const REQUEST_POST_CHUNKED: &str = "POST /lorem HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nipsum\r\n6;ext=1\r\n dolor\r\n0\r\nTrailer: 1\r\n\r\n";
 
     struct StringRead<'a> {
         iter: std::slice::Iter<'a, u8>,
@@ -218,19 +246,265 @@ mod tests {
         }
     }
 
+    mod body {
+        #[test]
+        fn parse_reader() {
+            use super::StringRead;
+            use super::Request;
+
+            let mut reader_request_post_length = StringRead::new(super::REQUEST_POST_LENGTH);
+            let mut reader_request_post_chunked = StringRead::new(super::REQUEST_POST_CHUNKED);
+
+            let mut request = Request::parse(&mut reader_request_post_length).ok().unwrap();
+            assert_eq!(Some("localhost"), request.get_headers().get("host"));
+            assert_eq!(Some(5usize), request.get_content_length());
+            assert!(request.is_expecting_continue());
+            assert_eq!(b"ipsum".to_vec(), request.get_body().read_to_vec().unwrap());
+
+            let mut request = Request::parse(&mut reader_request_post_chunked).ok().unwrap();
+            assert!(!request.is_expecting_continue());
+            assert_eq!(b"ipsum dolor".to_vec(), request.get_body().read_to_vec().unwrap());
+        }
+    }
+
+    mod borrowed {
+        #[test]
+        fn parse_buffer() {
+            use super::Request;
+            use super::RequestRef;
+            use super::ParseMode;
+            use super::Limits;
+
+            let limits = Limits::new();
+
+            let buffer = b"POST /lorem/ HTTP/1.1\r\nHost: localhost\r\nContent-Length: 5\r\n\r\nipsum";
+
+            assert!(RequestRef::parse(&buffer[..32usize], ParseMode::Strict, &limits).ok().unwrap().is_none());
+
+            let request = RequestRef::parse(buffer, ParseMode::Strict, &limits).ok().unwrap().unwrap();
+            assert_eq!(super::Method::POST, *request.get_method());
+            assert_eq!("/lorem", request.get_target());
+            assert_eq!(Some("localhost"), request.get_header("HOST"));
+            assert_eq!(2usize, request.get_headers().len());
+            assert_eq!(Some(5usize), request.get_content_length());
+            assert_eq!(b"ipsum", &buffer[request.get_head_length()..]);
+
+            let target = request.get_target();
+            assert_eq!(buffer[5usize..].as_ptr(), target.as_ptr());
+
+            let request = Request::from(request);
+            assert_eq!("/lorem", request.get_target());
+            assert_eq!(Some("localhost"), request.get_headers().get("Host"));
+
+            let buffer = b"GET / HTTP/1.1\r\nX-Padding: lorem\r\n\tipsum\r\n\r\n";
+
+            let request = RequestRef::parse(buffer, ParseMode::Lenient, &limits).ok().unwrap().unwrap();
+            assert_eq!(Some("lorem\r\n\tipsum"), request.get_header("X-Padding"));
+            assert_eq!(Some("lorem ipsum"), Request::from(request).get_headers().get("X-Padding"));
+        }
+    }
+
+    mod smuggling {
+        use super::StringRead;
+        use super::Request;
+        use super::ParseMode;
+        use super::Limits;
+
+        // Payloads that parsers disagree on; the strict parser must reject every one of them.
+        const PAYLOADS: &[&str] = &[
+            // CL.TE / TE.CL
+            "POST / HTTP/1.1\r\nContent-Length: 6\r\nTransfer-Encoding: chunked\r\n\r\n0\r\n\r\nG",
+            "POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\nContent-Length: 3\r\n\r\n8\r\nSMUGGLED\r\n0\r\n\r\n",
+            // Duplicate, differing Content-Length
+            "POST / HTTP/1.1\r\nContent-Length: 0\r\nContent-Length: 5\r\n\r\nGPOST",
+            "POST / HTTP/1.1\r\nContent-Length: 0, 5\r\n\r\nGPOST",
+            // Malformed Content-Length
+            "POST / HTTP/1.1\r\nContent-Length: +5\r\n\r\nGPOST",
+            "POST / HTTP/1.1\r\nContent-Length: 0x5\r\n\r\nGPOST",
+            "POST / HTTP/1.1\r\nContent-Length: 5 5\r\n\r\nGPOST",
+            "POST / HTTP/1.1\r\nContent-Length:\r\n\r\n",
+            // Obfuscated Transfer-Encoding
+            "POST / HTTP/1.1\r\nTransfer-Encoding: xchunked\r\n\r\n0\r\n\r\n",
+            "POST / HTTP/1.1\r\nTransfer-Encoding: chunked, identity\r\n\r\n0\r\n\r\n",
+            "POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\nTransfer-Encoding: chunked\r\n\r\n0\r\n\r\n",
+            "POST / HTTP/1.1\r\nTransfer-Encoding\x0b: chunked\r\n\r\n0\r\n\r\n",
+            "POST / HTTP/1.1\r\nTransfer-Encoding: \x0bchunked\r\n\r\n0\r\n\r\n",
+            // Whitespace before the colon
+            "POST / HTTP/1.1\r\nTransfer-Encoding : chunked\r\n\r\n0\r\n\r\n",
+            "POST / HTTP/1.1\r\nContent-Length\t: 5\r\n\r\nGPOST",
+            // obs-fold
+            "POST / HTTP/1.1\r\nX-Padding: a\r\n Transfer-Encoding: chunked\r\n\r\n0\r\n\r\n",
+            "POST / HTTP/1.1\r\nContent-Length: 0\r\n\t5\r\n\r\nGPOST",
+            // Bare LF and bare CR
+            "POST / HTTP/1.1\nContent-Length: 5\n\nGPOST",
+            "POST / HTTP/1.1\r\nX-Padding: a\nContent-Length: 5\r\n\r\nGPOST",
+            "POST / HTTP/1.1\r\nX-Padding: a\rContent-Length: 5\r\n\r\nGPOST",
+            // Invalid tokens
+            "P0ST / HTTP/1.1\r\n\r\n",
+            "GET\t/ HTTP/1.1\r\n\r\n",
+            "POST / HTTP/1.1\r\nContent Length: 5\r\n\r\nGPOST",
+            "POST / HTTP/1.1\r\n: 5\r\n\r\n",
+            "POST / HTTP/1.1\r\nContent-Length\x00: 5\r\n\r\nGPOST",
+            "GET /\x7f HTTP/1.1\r\n\r\n",
+            "GET / HTTP/1.1 \r\n\r\n",
+            "GET  / HTTP/1.1\r\n\r\n",
+            // Duplicate Host
+            "GET / HTTP/1.1\r\nHost: a\r\nHost: b\r\n\r\n",
+            // Leading empty line
+            "\r\nGET / HTTP/1.1\r\n\r\n",
+        ];
+
+        // Legacy framing that the lenient parser accepts, with the body it ends up reading.
+        const PAYLOADS_LENIENT: &[(&str, &str)] = &[
+            ("POST / HTTP/1.1\r\nContent-Length: 6\r\nTransfer-Encoding: chunked\r\n\r\n1\r\nG\r\n0\r\n\r\n", "G"),
+            ("POST / HTTP/1.1\nContent-Length: 5\n\nGPOST", "GPOST"),
+            ("POST / HTTP/1.1\r\nContent-Length : 5\r\n\r\nGPOST", "GPOST"),
+            ("POST / HTTP/1.1\r\nX-Padding: a\r\n b\r\nContent-Length: 5\r\n\r\nGPOST", "GPOST"),
+            ("\r\n\nPOST / HTTP/1.1\r\nContent-Length: 5, 5\r\n\r\nGPOST", "GPOST"),
+        ];
+
+        #[test]
+        fn parse_strict() {
+            for payload in PAYLOADS.iter() {
+                let mut reader = StringRead::new(payload);
+
+                assert!(Request::parse(&mut reader).is_err(), "accepted {:?}", payload);
+            }
+        }
+
+        #[test]
+        fn parse_lenient() {
+            for (payload, body) in PAYLOADS_LENIENT.iter() {
+                let mut reader = StringRead::new(payload);
+
+                let request = Request::parse_with(&mut reader, ParseMode::Lenient, &Limits::new());
+                assert!(request.is_ok(), "rejected {:?}", payload);
+
+                let mut request = request.ok().unwrap();
+                assert_eq!(body.as_bytes().to_vec(), request.get_body().read_to_vec().unwrap());
+            }
+
+            // Transfer-Encoding wins over Content-Length.
+            let mut reader = StringRead::new(PAYLOADS_LENIENT[0usize].0);
+            let request = Request::parse_with(&mut reader, ParseMode::Lenient, &Limits::new()).ok().unwrap();
+            assert_eq!(None, request.get_content_length());
+            assert!(!request.get_headers().contains("Content-Length"));
+
+            let mut reader = StringRead::new("POST / HTTP/1.1\r\nX-Padding: a\r\n\tb\r\n\r\n");
+            let request = Request::parse_with(&mut reader, ParseMode::Lenient, &Limits::new()).ok().unwrap();
+            assert_eq!(Some("a b"), request.get_headers().get("X-Padding"));
+        }
+    }
+
+    mod limits {
+        #[test]
+        fn parse_reader() {
+            use super::StringRead;
+            use super::Request;
+            use super::ParseMode;
+            use super::Limits;
+            use super::Error;
+
+            let mut limits = Limits::new();
+            limits.set_max_length_start_line(32usize);
+            limits.set_max_length_header(32usize);
+            limits.set_max_count_header(2usize);
+            limits.set_max_length_head(64usize);
+            limits.set_max_length_body(4usize);
+
+            let parse = |request: &str| Request::parse_with(&mut StringRead::new(request), ParseMode::Strict, &limits);
+
+            assert!(parse("GET /lorem/ipsum HTTP/1.1\r\nA: 1\r\nB: 2\r\n\r\n").is_ok());
+
+            match parse("GET /lorem/ipsum/dolor/sit/amet HTTP/1.1\r\n\r\n") {
+                Err(Error::URITooLong) => {}
+                _ => panic!("expected URITooLong"),
+            }
+
+            match parse("GET / HTTP/1.1\r\nX-Lorem: ipsum dolor sit amet consectetur\r\n\r\n") {
+                Err(Error::HeaderFieldsTooLarge) => {}
+                _ => panic!("expected HeaderFieldsTooLarge"),
+            }
+
+            match parse("GET / HTTP/1.1\r\nA: 1\r\nB: 2\r\nC: 3\r\n\r\n") {
+                Err(Error::HeaderFieldsTooLarge) => {}
+                _ => panic!("expected HeaderFieldsTooLarge"),
+            }
+
+            match parse("GET / HTTP/1.1\r\nX-Lorem: ipsum dolor\r\nX-Ipsum: lorem dolor\r\nX") {
+                Err(Error::HeaderFieldsTooLarge) => {}
+                _ => panic!("expected HeaderFieldsTooLarge"),
+            }
+
+            match parse("POST / HTTP/1.1\r\nContent-Length: 5\r\n\r\nipsum") {
+                Err(Error::PayloadTooLarge) => {}
+                _ => panic!("expected PayloadTooLarge"),
+            }
+
+            let mut request = parse("POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nipsum\r\n0\r\n\r\n").ok().unwrap();
+            assert!(request.get_body().read_to_vec().is_err());
+        }
+    }
+
     mod benchmarks {
         use test::Bencher;
 
         const REQUEST_LINE: &str = "HEAD /lorem/ipsum/dolor/sit/amet HTTP/1.1\r\n\r\n";
 
+        const REQUEST_HEADERS: &str = "GET /lorem/ipsum/dolor/sit/amet?consectetur=adipiscing HTTP/1.1\r\n\
+            Host: lorem.ipsum.example\r\n\
+            User-Agent: Mozilla/5.0 (X11; Linux x86_64; rv:68.0) Gecko/20100101 Firefox/68.0\r\n\
+            Accept: text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8\r\n\
+            Accept-Language: en-US,en;q=0.5\r\n\
+            Accept-Encoding: gzip, deflate, br\r\n\
+            Referer: https://lorem.ipsum.example/dolor/sit/amet\r\n\
+            Connection: keep-alive\r\n\
+            Cookie: session=0123456789abcdef0123456789abcdef; theme=dark; language=en\r\n\
+            Upgrade-Insecure-Requests: 1\r\n\
+            Cache-Control: max-age=0\r\n\
+            If-None-Match: \"0123456789abcdef\"\r\n\
+            If-Modified-Since: Sat, 01 Jun 2019 00:00:00 GMT\r\n\
+            X-Forwarded-For: 192.0.2.1, 198.51.100.1\r\n\
+            X-Forwarded-Proto: https\r\n\
+            X-Request-Id: 0123456789abcdef\r\n\
+            DNT: 1\r\n\r\n";
+
         #[bench]
         fn parse_reader(b: &mut Bencher) {
             use super::Request;
             use super::StringRead;
 
-            let mut reader = StringRead::new(REQUEST_LINE);
+            b.iter(|| test::black_box(Request::parse(&mut StringRead::new(REQUEST_LINE))));
+        }
+
+        #[bench]
+        fn parse_reader_headers(b: &mut Bencher) {
+            use super::Request;
+            use super::StringRead;
+
+            b.iter(|| test::black_box(Request::parse(&mut StringRead::new(REQUEST_HEADERS))));
+        }
+
+        #[bench]
+        fn parse_borrowed(b: &mut Bencher) {
+            use super::RequestRef;
+            use super::ParseMode;
+            use super::Limits;
+
+            let limits = Limits::new();
+
+            b.iter(|| test::black_box(RequestRef::parse(REQUEST_LINE.as_bytes(), ParseMode::Strict, &limits).ok()));
+        }
+
+        #[bench]
+        fn parse_borrowed_headers(b: &mut Bencher) {
+            use super::RequestRef;
+            use super::ParseMode;
+            use super::Limits;
+
+            let limits = Limits::new();
 
-            b.iter(|| test::black_box(Request::parse(&mut reader)));
+            b.iter(|| test::black_box(RequestRef::parse(REQUEST_HEADERS.as_bytes(), ParseMode::Strict, &limits).ok()));
         }
     }
 }
@@ -1,15 +1,85 @@
+use std::io;
+use std::io::Read;
+use std::io::Seek;
 use std::fmt;
 
 use crate::http;
+use crate::http::Headers;
+use crate::http::Result;
+
+use crate::http::codec;
+use crate::http::codec::Framing;
+use crate::http::codec::BodyEncoder;
+
+const LENGTH_READ: usize = 65536usize;
+
+pub(crate) trait ReadSeek: io::Read + io::Seek {}
+
+impl<T: io::Read + io::Seek> ReadSeek for T {}
+
+// Where a streamed body is read from, seekable ones letting ranges of it be sent without reading what precedes them.
+pub(crate) enum Source {
+    Stream(Box<dyn io::Read>),
+    Seekable(Box<dyn ReadSeek>),
+}
+
+impl Source {
+    pub(crate) fn is_seekable(&self) -> bool {
+        return matches!(self, Source::Seekable(_));
+    }
+
+    // Moves from `position` forward to `target`, reading and dropping what is in between when not seekable.
+    pub(crate) fn skip(&mut self, position: u64, target: u64) -> io::Result<()> {
+        match self {
+            Source::Stream(_) if target < position => {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "cannot seek backwards in a stream"));
+            }
+            Source::Stream(reader) => {
+                let skipped = io::copy(&mut reader.take(target - position), &mut io::sink())?;
+
+                if skipped < target - position {
+                    return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+                }
+            }
+            Source::Seekable(reader) => {
+                reader.seek(io::SeekFrom::Start(target))?;
+            }
+        }
+
+        return Ok(());
+    }
+}
+
+impl io::Read for Source {
+    fn read(&mut self, output: &mut [u8]) -> io::Result<usize> {
+        return match self {
+            Source::Stream(reader) => reader.read(output),
+            Source::Seekable(reader) => reader.read(output),
+        };
+    }
+}
 
 pub struct Response {
     status: http::Status,
+
+    headers: Headers,
+
+    body: Vec<u8>,
+
+    // Streamed in place of `body` when set, along with its length if known.
+    reader: Option<(Source, Option<usize>)>,
 }
 
 impl Response {
     pub fn new() -> Self {
         return Self {
             status: http::Status::OK,
+
+            headers: Headers::new(),
+
+            body: Vec::new(),
+
+            reader: None,
         };
     }
 
@@ -20,10 +90,154 @@ impl Response {
     pub fn set_status(&mut self, status: http::Status) {
         self.status = status;
     }
+
+    pub fn get_headers(&self) -> &Headers {
+        return &self.headers;
+    }
+
+    pub fn add_header(&mut self, name: &str, value: &str) {
+        self.headers.add(name, value);
+    }
+
+    pub fn set_header(&mut self, name: &str, value: &str) {
+        self.headers.set(name, value);
+    }
+
+    pub fn remove_header(&mut self, name: &str) {
+        self.headers.remove(name);
+    }
+
+    pub fn get_body(&self) -> &[u8] {
+        return &self.body;
+    }
+
+    pub fn set_body<B: Into<Vec<u8>>>(&mut self, body: B) {
+        self.body = body.into();
+        self.reader = None;
+    }
+
+    // Streams the body from `reader` as it is sent, chunked when its length is not known.
+    pub fn set_reader(&mut self, reader: Box<dyn io::Read>, length: Option<usize>) {
+        self.body.clear();
+        self.reader = Some((Source::Stream(reader), length));
+    }
+
+    // Same with a reader starting at the beginning of the body, which ranges of it can be sent from directly.
+    pub fn set_seekable_reader<R: io::Read + io::Seek + 'static>(&mut self, reader: R, length: usize) {
+        self.body.clear();
+        self.reader = Some((Source::Seekable(Box::new(reader)), Some(length)));
+    }
+
+    // The body, streamed or not, leaving it empty.
+    pub(crate) fn take_source(&mut self) -> Source {
+        return match self.reader.take() {
+            Some((source, _)) => source,
+            None => Source::Seekable(Box::new(io::Cursor::new(std::mem::take(&mut self.body)))),
+        };
+    }
+
+    pub fn is_streaming(&self) -> bool {
+        return self.reader.is_some();
+    }
+
+    // Length of the body, None when streamed with an unknown length.
+    pub fn get_content_length(&self) -> Option<usize> {
+        return match &self.reader {
+            Some((_, length)) => *length,
+            None => Some(self.body.len()),
+        };
+    }
+
+    // Whether the status allows a body at all (RFC 7230 3.3.3).
+    fn has_body(&self) -> bool {
+        let code = self.status.get_code();
+
+        return code >= 200usize && code != 204usize && code != 304usize;
+    }
+
+    // Headers as sent, with the "Content-Length" of the body unless the handler framed it itself.
+    fn get_head_headers(&self) -> Headers {
+        let mut headers = Headers::new();
+
+        for (name, value) in self.headers.iter() {
+            headers.add(name, value);
+        }
+
+        if self.has_body() && !headers.contains("Content-Length") && !headers.contains("Transfer-Encoding") {
+            match self.get_content_length() {
+                Some(length) => headers.add("Content-Length", &length.to_string()),
+                None => headers.add("Transfer-Encoding", "chunked"),
+            }
+        }
+
+        return headers;
+    }
+
+    pub fn encode_head(&self, output: &mut Vec<u8>) {
+        codec::encode_response_head(output, &self.status, &self.get_head_headers());
+    }
+
+    // Nothing for statuses that cannot have a body.
+    pub fn encode_body(&self, output: &mut Vec<u8>) {
+        if self.has_body() {
+            output.extend_from_slice(&self.body);
+        }
+    }
+
+    // Sends the streamed body, if any, returning the number of body bytes read from it.
+    pub(crate) fn write_reader(&mut self, writer: &mut dyn io::Write) -> Result<usize> {
+        let (source, length) = match self.reader.take() {
+            Some(reader) if self.has_body() => reader,
+            _ => return Ok(0usize),
+        };
+
+        // Nothing past the announced length, which a file growing while it is sent would otherwise add.
+        let mut reader = source.take(length.map_or(u64::MAX, |length| length as u64));
+
+        let mut encoder = BodyEncoder::new(match length {
+            Some(length) => Framing::Length(length),
+            None => Framing::Chunked,
+        });
+
+        let mut buffer = vec![0u8; LENGTH_READ];
+        let mut output = Vec::new();
+        let mut written = 0usize;
+
+        loop {
+            let read = reader.read(&mut buffer)?;
+
+            if read == 0usize {
+                break;
+            }
+
+            encoder.encode(&buffer[..read], &mut output)?;
+            written += read;
+
+            writer.write_all(&output)?;
+            output.clear();
+        }
+
+        // Fails when the reader ended before the announced length, the connection is closed anyway.
+        encoder.finish(&mut output)?;
+
+        writer.write_all(&output)?;
+        writer.flush()?;
+
+        return Ok(written);
+    }
+
+    // The body held in memory only, see `write_reader` for streamed ones.
+    pub fn encode(&self, output: &mut Vec<u8>) {
+        self.encode_head(output);
+        self.encode_body(output);
+    }
 }
 
 impl fmt::Display for Response {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        return formatter.write_fmt(format_args!("HTTP/1.1 {}\r\n\r\n", self.status));
+        let mut head = Vec::new();
+        self.encode_head(&mut head);
+
+        return formatter.write_str(&String::from_utf8_lossy(&head));
     }
 }
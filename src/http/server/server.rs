@@ -8,10 +8,41 @@ use std::net::TcpListener;
 
 use std::io::Write;
 
+use std::sync::Arc;
+
+use std::collections::HashMap;
+
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+
 use super::Result;
+use super::Error;
+
+use super::Handler;
+use super::Middleware;
+use super::Pipeline;
+use super::Request;
+use super::ParseMode;
+use super::Response;
+use super::AccessEntry;
+use super::AccessLogger;
+use super::Metrics;
+use super::ErrorRenderer;
+
+use super::metrics;
+use super::metrics::CountingReader;
+use super::metrics::InFlight;
+
+use super::panic;
+use super::range;
 
 use crate::http::Status;
 use crate::http::Method;
+use crate::http::Limits;
+
+use crate::http::diagnostics;
+use crate::http::request_id;
 
 const DEFAULT_ADDRESS: u32 = 0x7F_00_00_01u32;
 const DEFAULT_PORT: u16 = 80u16;
@@ -19,6 +50,47 @@ const DEFAULT_PORT: u16 = 80u16;
 pub struct Server {
     address: u32,
     port: u16,
+
+    limits: Limits,
+
+    parse_mode: ParseMode,
+
+    pipeline: Pipeline,
+
+    // None to log nothing.
+    access_logger: Option<Box<dyn AccessLogger>>,
+
+    // Requests taking longer are reported as warnings.
+    slow_request_threshold: Option<Duration>,
+
+    // Whether an "X-Request-Id" sent by the client is kept, as when a trusted proxy in front sets it.
+    request_id_trusted: bool,
+
+    metrics: Arc<Metrics>,
+    // Where the metrics are served, not at all when None.
+    metrics_path: Option<String>,
+
+    // Whether a panicking handler takes the process down, rather than getting a 500 response.
+    abort_on_panic: bool,
+
+    // By status code, `default_error_renderer` covering the others.
+    error_renderers: HashMap<usize, Box<dyn ErrorRenderer>>,
+    default_error_renderer: Option<Box<dyn ErrorRenderer>>,
+}
+
+fn default_handler(request: &mut Request) -> Response {
+    let mut response = Response::new();
+
+    match request.get_method() {
+        Method::GET => {
+            response.set_status(Status::OK);
+        }
+        _ => {
+            response.set_status(Status::NotFound);
+        }
+    }
+
+    return response;
 }
 
 impl Server {
@@ -26,6 +98,26 @@ impl Server {
         return Self {
             address: DEFAULT_ADDRESS,
             port: DEFAULT_PORT,
+
+            limits: Limits::new(),
+
+            parse_mode: ParseMode::Strict,
+
+            pipeline: Pipeline::new(default_handler),
+
+            access_logger: None,
+
+            slow_request_threshold: None,
+
+            request_id_trusted: false,
+
+            metrics: Arc::new(Metrics::new()),
+            metrics_path: None,
+
+            abort_on_panic: false,
+
+            error_renderers: HashMap::new(),
+            default_error_renderer: None,
         };
     }
 
@@ -38,14 +130,22 @@ impl Server {
             let stream = stream;
 
             match stream {
-                Ok(stream) => match self.handle(stream) {
-                    Ok(_) => {}
-                    Err(err) => {
-                        println!("{}", err);
+                Ok(stream) => {
+                    let peer = stream.peer_addr().ok();
+
+                    match self.handle(stream) {
+                        Ok(_) => {}
+                        // Requests the client got wrong are expected now and then, failures on this side are not.
+                        Err(Error::InternalError(err)) => {
+                            diagnostics::error!("failed to handle a connection from {:?}: {}", peer, err);
+                        }
+                        Err(err) => {
+                            diagnostics::warning!("rejected a request from {:?}: {:?}", peer, err);
+                        }
                     }
                 }
                 Err(err) => {
-                    println!("{}", err);
+                    diagnostics::error!("failed to accept a connection: {}", err);
                 }
             }
         }
@@ -54,26 +154,293 @@ impl Server {
     }
 
     fn handle(&self, mut stream: TcpStream) -> Result<()> {
-        let request = super::Request::parse(&mut stream)?;
+        let time = SystemTime::now();
+        let start = Instant::now();
+
+        let _in_flight = InFlight::enter(&self.metrics);
+
+        // A connection closed before sending anything, as by health checks, is no failed request.
+        if let Ok(0usize) = stream.peek(&mut [0u8; 1usize]) {
+            return Ok(());
+        }
+
+        let (mut request, error) = match Request::parse_with(&mut CountingReader::new(&stream, &self.metrics), self.parse_mode, &self.limits) {
+            Ok(request) => (Some(request), None),
+            Err(err) => {
+                self.metrics.add_parse_failure(&err);
+
+                (None, Some(err))
+            }
+        };
+
+        if let Some(request) = request.as_mut() {
+            let id = match request.get_headers().get(request_id::HEADER_REQUEST_ID) {
+                Some(id) if self.request_id_trusted && request_id::is_valid(id) => String::from(id),
+                _ => request_id::generate(),
+            };
+
+            request.set_id(id);
+        }
 
-        let mut response = super::Response::new();
+        // Everything emitted while handling the request is attributed to it.
+        #[cfg(feature = "tracing")]
+        let _span = request.as_ref().map(|request| tracing::info_span!("request", request_id = request.get_id(), method = %request.get_method(), target = request.get_target()).entered());
+
+        let mut response = match request.as_mut() {
+            Some(request) => self.respond(&mut stream, request)?,
+            None => {
+                let status = match &error {
+                    // The connection failed, there is no one left to answer.
+                    Some(Error::InternalError(_)) | None => return Err(error.unwrap_or(Error::BadRequest)),
+                    Some(err) => err.get_status(),
+                };
+
+                let mut response = Response::new();
+                response.set_status(status);
+
+                response
+            }
+        };
+
+        if response.get_status().get_code() >= 400usize && response.get_body().is_empty() && !response.is_streaming() {
+            if let Some(error_renderer) = self.get_error_renderer(response.get_status()) {
+                error_renderer.render(request.as_ref(), &mut response);
+            }
+        }
+
+        // Ranges apply once error pages are rendered, which are for the handler's errors: a 416 answering a range has no page.
+        if let Some(request) = &request {
+            range::apply(request, &mut response);
+        }
+
+        if let Some(request) = &request {
+            if !response.get_headers().contains(request_id::HEADER_REQUEST_ID) {
+                response.set_header(request_id::HEADER_REQUEST_ID, request.get_id());
+            }
+        }
+
+        // Connections are not kept alive, which clients must know now that responses are framed.
+        if !response.get_headers().contains("Connection") {
+            response.set_header("Connection", "close");
+        }
+
+        let mut output = Vec::new();
+        response.encode_head(&mut output);
+
+        let length_head = output.len();
+        let head_only = request.as_ref().is_some_and(|request| *request.get_method() == Method::HEAD);
+
+        if !head_only {
+            response.encode_body(&mut output);
+        }
+
+        let written = stream.write_all(&output);
+
+        // A client sending a malformed request may well be gone already, the parse error is what matters then.
+        if error.is_none() {
+            written?;
+        }
+
+        let mut bytes_sent = output.len() - length_head;
+
+        if !head_only && error.is_none() {
+            bytes_sent += response.write_reader(&mut stream)?;
+        }
+
+        let latency = start.elapsed();
+
+        self.metrics.add_bytes_sent(length_head + bytes_sent);
+
+        if let Some(request) = &request {
+            self.metrics.add_request(request.get_method(), response.get_status(), latency);
+
+            if self.slow_request_threshold.is_some_and(|threshold| latency > threshold) {
+                diagnostics::warning!("slow request: {} {} answered {} in {:?}", request.get_method(), request.get_target(), response.get_status().get_code(), latency);
+            } else {
+                diagnostics::debug!("{} {} answered {} in {:?}", request.get_method(), request.get_target(), response.get_status().get_code(), latency);
+            }
+        }
+
+        if let Some(access_logger) = &self.access_logger {
+            let entry = AccessEntry::new(stream.peer_addr().ok(), time, request.as_ref(), &response, bytes_sent, latency);
+
+            access_logger.log(&entry);
+        }
+
+        return match error {
+            Some(err) => Err(err),
+            None => Ok(()),
+        };
+    }
+
+    fn respond(&self, stream: &mut TcpStream, request: &mut Request) -> Result<Response> {
+        if request.get_headers().contains("Expect") && !request.is_expecting_continue() {
+            let mut response = Response::new();
+            response.set_status(Status::ExpectationFailed);
+
+            return Ok(response);
+        }
+
+        // The interim "100 Continue" is only sent once the handler starts reading the body.
+        let interim: Option<Box<dyn Write>> = if request.is_expecting_continue() {
+            Some(Box::new(stream.try_clone()?))
+        } else {
+            None
+        };
+
+        request.get_body().attach(Box::new(CountingReader::new(stream.try_clone()?, &self.metrics)), interim);
+
+        // Requests sent with `Client` by the handler carry the same ID.
+        let _scope = request_id::Scope::enter(request.get_id());
+
+        // The metrics go through the middlewares like any other path, so that they can be protected the same way.
+        let metrics = self.metrics_path.as_deref() == Some(request.get_path());
+
+        let handle = || {
+            if metrics {
+                return self.pipeline.handle_with(request, &|request: &mut Request| self.get_metrics_response(request));
+            }
+
+            return self.pipeline.handle(request);
+        };
+
+        return match panic::catch(handle) {
+            Ok(response) => Ok(response),
+            Err(panic) => {
+                let backtrace = panic.get_backtrace().map(|backtrace| backtrace.to_string()).unwrap_or_default();
+
+                diagnostics::error!("handler panicked at {}: {}\n{}", panic.get_location(), panic.get_message(), backtrace);
+
+                if self.abort_on_panic {
+                    std::process::abort();
+                }
+
+                let mut response = Response::new();
+                response.set_status(Status::InternalServerError);
+
+                Ok(response)
+            }
+        };
+    }
+
+    // Served in place of the handler, behind the middlewares.
+    fn get_metrics_response(&self, request: &Request) -> Response {
+        let mut response = Response::new();
 
         match request.get_method() {
-            Method::GET => {
-                response.set_status(Status::OK);
+            Method::GET | Method::HEAD => {
+                response.set_header("Content-Type", metrics::CONTENT_TYPE_METRICS);
+                response.set_body(self.metrics.render());
             }
             _ => {
-                response.set_status(Status::NotFound);
+                response.set_status(Status::MethodNotAllowed);
+                response.set_header("Allow", "GET, HEAD");
             }
         }
 
-        print!("Request:\n{}", request);
-        print!("Response:\n{}", response);
-        println!("----");
+        return response;
+    }
 
-        stream.write(response.to_string().as_bytes())?;
+    fn get_error_renderer(&self, status: &Status) -> Option<&dyn ErrorRenderer> {
+        return match self.error_renderers.get(&status.get_code()) {
+            Some(error_renderer) => Some(error_renderer.as_ref()),
+            None => self.default_error_renderer.as_deref(),
+        };
+    }
 
-        return Ok(());
+    pub fn set_handler<H: Handler + 'static>(&mut self, handler: H) {
+        self.pipeline.set_handler(handler);
+    }
+
+    // Middlewares run around the handler in the order they are added, see `Pipeline`.
+    pub fn add_middleware<M: Middleware + 'static>(&mut self, middleware: M) {
+        self.pipeline.add(middleware);
+    }
+
+    pub fn add_route_middleware<M: Middleware + 'static>(&mut self, route: &str, middleware: M) {
+        self.pipeline.add_route(route, middleware);
+    }
+
+    // Receives an entry once every response is written, see `AccessLog` for the usual log formats.
+    pub fn set_access_logger<L: AccessLogger + 'static>(&mut self, access_logger: L) {
+        self.access_logger = Some(Box::new(access_logger));
+    }
+
+    pub fn clear_access_logger(&mut self) {
+        self.access_logger = None;
+    }
+
+    pub fn set_slow_request_threshold(&mut self, slow_request_threshold: Option<Duration>) {
+        self.slow_request_threshold = slow_request_threshold;
+    }
+
+    pub fn get_slow_request_threshold(&self) -> Option<Duration> {
+        return self.slow_request_threshold;
+    }
+
+    // Keeps the "X-Request-Id" of incoming requests instead of generating a new ID, when it is well-formed.
+    // Only to enable behind a proxy that sets or strips that header.
+    pub fn set_request_id_trusted(&mut self, request_id_trusted: bool) {
+        self.request_id_trusted = request_id_trusted;
+    }
+
+    pub fn is_request_id_trusted(&self) -> bool {
+        return self.request_id_trusted;
+    }
+
+    pub fn get_metrics(&self) -> &Arc<Metrics> {
+        return &self.metrics;
+    }
+
+    // Serves the metrics in the Prometheus text format at `metrics_path`, such as "/metrics".
+    // Requests for it still go through the middlewares, which can restrict who reads them.
+    pub fn set_metrics_path(&mut self, metrics_path: Option<&str>) {
+        self.metrics_path = metrics_path.map(String::from);
+    }
+
+    pub fn get_metrics_path(&self) -> Option<&str> {
+        return self.metrics_path.as_deref();
+    }
+
+    // Aborts the process when a handler panics, for deployments that rather restart than keep running after one.
+    pub fn set_abort_on_panic(&mut self, abort_on_panic: bool) {
+        self.abort_on_panic = abort_on_panic;
+    }
+
+    pub fn is_aborting_on_panic(&self) -> bool {
+        return self.abort_on_panic;
+    }
+
+    // Renders the error responses with `status` that have no body, including those to requests that could not be parsed,
+    // such as with `render_html` or `render_problem`.
+    pub fn set_error_renderer<R: ErrorRenderer + 'static>(&mut self, status: Status, error_renderer: R) {
+        self.error_renderers.insert(status.get_code(), Box::new(error_renderer));
+    }
+
+    // Renders the error responses no renderer was set for with `set_error_renderer`.
+    pub fn set_default_error_renderer<R: ErrorRenderer + 'static>(&mut self, error_renderer: R) {
+        self.default_error_renderer = Some(Box::new(error_renderer));
+    }
+
+    pub fn clear_error_renderers(&mut self) {
+        self.error_renderers.clear();
+        self.default_error_renderer = None;
+    }
+
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    pub fn get_limits(&self) -> &Limits {
+        return &self.limits;
+    }
+
+    pub fn set_parse_mode(&mut self, parse_mode: ParseMode) {
+        self.parse_mode = parse_mode;
+    }
+
+    pub fn get_parse_mode(&self) -> ParseMode {
+        return self.parse_mode;
     }
 
     pub fn set_port(&mut self, port: u16) {
@@ -92,3 +459,289 @@ impl Server {
         self.address
     }
 }
+
+#[cfg(test)]
+mod tests {
+    mod assertions {
+        use std::io::Read;
+        use std::io::Write;
+
+        use std::net::TcpListener;
+        use std::net::TcpStream;
+
+        use std::thread;
+
+        #[cfg(feature = "tracing")]
+        use std::fmt;
+        #[cfg(feature = "tracing")]
+        use std::sync::Arc;
+        #[cfg(feature = "tracing")]
+        use std::sync::Mutex;
+
+        use crate::http::Error;
+        use crate::http::Status;
+        use crate::http::Limits;
+
+        use crate::http::server::before;
+        use crate::http::server::render_html;
+
+        use super::super::Server;
+        use super::super::Request;
+        use super::super::Response;
+
+        use super::super::metrics;
+
+        // Handles one connection with `server`, `client` running on the other end of it.
+        fn serve<F: FnOnce(TcpStream) -> R + Send + 'static, R: Send + 'static>(server: &Server, client: F) -> R {
+            let listener = TcpListener::bind("127.0.0.1:0").ok().unwrap();
+            let address = listener.local_addr().ok().unwrap();
+
+            let client = thread::spawn(move || client(TcpStream::connect(address).ok().unwrap()));
+
+            let (stream, _) = listener.accept().ok().unwrap();
+            let _ = server.handle(stream);
+
+            return client.join().ok().unwrap();
+        }
+
+        // Sends `request` as is and reads the response to the end.
+        fn exchange(server: &Server, request: &str) -> String {
+            let request = String::from(request);
+
+            return serve(server, move |mut stream| {
+                stream.write_all(request.as_bytes()).ok().unwrap();
+
+                let mut response = String::new();
+                stream.read_to_string(&mut response).ok().unwrap();
+
+                return response;
+            });
+        }
+
+        #[test]
+        fn expect_continue() {
+            let mut server = Server::new();
+            server.set_handler(|request: &mut Request| {
+                let mut response = Response::new();
+
+                if request.get_path() == "/upload" {
+                    response.set_body(request.get_body().read_to_vec().ok().unwrap());
+                }
+
+                return response;
+            });
+
+            // The body is only sent once "100 Continue" came, which the handler reading it triggers.
+            let response = serve(&server, |mut stream| {
+                stream.write_all(b"POST /upload HTTP/1.1\r\nContent-Length: 5\r\nExpect: 100-continue\r\n\r\n").ok().unwrap();
+
+                let mut interim = [0u8; 25usize];
+                stream.read_exact(&mut interim).ok().unwrap();
+                assert_eq!(&interim[..], b"HTTP/1.1 100 Continue\r\n\r\n");
+
+                stream.write_all(b"hello").ok().unwrap();
+
+                let mut response = String::new();
+                stream.read_to_string(&mut response).ok().unwrap();
+
+                return response;
+            });
+
+            assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+            assert!(response.ends_with("\r\n\r\nhello"));
+
+            // A handler answering without reading the body never asks for it.
+            let response = exchange(&server, "POST /ignored HTTP/1.1\r\nContent-Length: 5\r\nExpect: 100-continue\r\n\r\n");
+
+            assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+            assert!(!response.contains("100 Continue"));
+
+            let response = exchange(&server, "POST /upload HTTP/1.1\r\nContent-Length: 5\r\nExpect: fancy-feature\r\n\r\n");
+
+            assert!(response.starts_with("HTTP/1.1 417 Expectation Failed\r\n"));
+            assert!(!response.contains("100 Continue"));
+        }
+
+        #[test]
+        fn metrics() {
+            let mut server = Server::new();
+            server.set_metrics_path(Some("/metrics"));
+            server.add_route_middleware("/metrics", before(|request: &mut Request| {
+                if request.get_headers().contains("Authorization") {
+                    return None;
+                }
+
+                let mut response = Response::new();
+                response.set_status(Status::Unauthorized);
+
+                return Some(response);
+            }));
+
+            let response = exchange(&server, "GET /metrics HTTP/1.1\r\n\r\n");
+
+            assert!(response.starts_with("HTTP/1.1 401 Unauthorized\r\n"));
+
+            let response = exchange(&server, "GET /metrics HTTP/1.1\r\nAuthorization: Bearer secret\r\n\r\n");
+
+            assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+            assert!(response.contains(&format!("\r\nContent-Type: {}\r\n", metrics::CONTENT_TYPE_METRICS)));
+            assert!(response.contains("\nbwf_http_requests_total{method=\"GET\",status=\"4xx\"} 1\n"));
+
+            let response = exchange(&server, "POST /metrics HTTP/1.1\r\nAuthorization: Bearer secret\r\nContent-Length: 0\r\n\r\n");
+
+            assert!(response.starts_with("HTTP/1.1 405 Method Not Allowed\r\n"));
+            assert!(response.contains("\r\nAllow: GET, HEAD\r\n"));
+        }
+
+        #[test]
+        fn closed_connection() {
+            let server = Server::new();
+
+            serve(&server, drop);
+
+            assert_eq!(server.get_metrics().get_parse_failures(&Error::BadRequest), 0u64);
+
+            exchange(&server, "\r\n");
+
+            assert_eq!(server.get_metrics().get_parse_failures(&Error::BadRequest), 1u64);
+        }
+
+        #[test]
+        fn error_status() {
+            let mut limits = Limits::new();
+            limits.set_max_length_start_line(64usize);
+            limits.set_max_length_header(64usize);
+            limits.set_max_length_body(4usize);
+
+            let mut server = Server::new();
+            server.set_limits(limits);
+
+            let long = "a".repeat(100usize);
+
+            for (request, status) in [
+                (String::from("GET / HTTP/1.1\r\nContent-Length: x\r\n\r\n"), "400 Bad Request"),
+                (String::from("POST / HTTP/1.1\r\nContent-Length: 5\r\n\r\nhello"), "413 Payload Too Large"),
+                (format!("GET /{} HTTP/1.1\r\n\r\n", long), "414 URI Too Long"),
+                (format!("GET / HTTP/1.1\r\nX-Long: {}\r\n\r\n", long), "431 Request Header Fields Too Large"),
+                (String::from("GET / HTTP/2.0\r\n\r\n"), "505 HTTP Version Not Supported"),
+            ] {
+                let response = exchange(&server, &request);
+
+                assert!(response.starts_with(&format!("HTTP/1.1 {}\r\n", status)), "answered {:?} with {:?}", request, response);
+            }
+        }
+
+        #[test]
+        fn error_renderers() {
+            let mut server = Server::new();
+            server.set_handler(|request: &mut Request| {
+                let mut response = Response::new();
+
+                match request.get_path() {
+                    "/gone" => response.set_status(Status::Gone),
+                    "/explained" => {
+                        response.set_status(Status::Conflict);
+                        response.set_body("already exists");
+                    }
+                    _ => response.set_status(Status::NotFound),
+                }
+
+                return response;
+            });
+
+            server.set_error_renderer(Status::NotFound, |_: Option<&Request>, response: &mut Response| response.set_body("not found"));
+            server.set_default_error_renderer(|request: Option<&Request>, response: &mut Response| {
+                response.set_body(format!("{} at {}", response.get_status().get_code(), request.map_or("-", |request| request.get_path())));
+            });
+
+            assert!(exchange(&server, "GET /missing HTTP/1.1\r\n\r\n").ends_with("\r\n\r\nnot found"));
+            assert!(exchange(&server, "GET /gone HTTP/1.1\r\n\r\n").ends_with("\r\n\r\n410 at /gone"));
+            assert!(exchange(&server, "GET / HTTP/2.0\r\n\r\n").ends_with("\r\n\r\n505 at -"));
+
+            // A body the handler gave is left alone.
+            let response = exchange(&server, "GET /explained HTTP/1.1\r\n\r\n");
+
+            assert!(response.starts_with("HTTP/1.1 409 Conflict\r\n"));
+            assert!(response.ends_with("\r\n\r\nalready exists"));
+
+            // Without renderers, error responses keep their empty body.
+            server.clear_error_renderers();
+
+            assert!(exchange(&server, "GET /missing HTTP/1.1\r\n\r\n").ends_with("\r\n\r\n"));
+        }
+
+        #[test]
+        fn range_not_satisfiable() {
+            let mut server = Server::new();
+            server.set_handler(|_: &mut Request| {
+                let mut response = Response::new();
+                response.set_body("0123456789");
+
+                return response;
+            });
+
+            server.set_default_error_renderer(render_html);
+
+            let response = exchange(&server, "GET / HTTP/1.1\r\nRange: bytes=20-\r\n\r\n");
+
+            assert!(response.starts_with("HTTP/1.1 416 Range Not Satisfiable\r\n"));
+            assert!(response.contains("\r\nContent-Range: bytes */10\r\n"));
+            assert!(response.contains("\r\nContent-Length: 0\r\n"));
+            assert!(response.ends_with("\r\n\r\n"));
+        }
+
+        // Collects the request IDs of the spans opened.
+        #[cfg(feature = "tracing")]
+        struct Spans(Arc<Mutex<Vec<String>>>);
+
+        #[cfg(feature = "tracing")]
+        struct RequestIdVisitor<'a>(&'a mut Vec<String>);
+
+        #[cfg(feature = "tracing")]
+        impl tracing::field::Visit for RequestIdVisitor<'_> {
+            fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+                if field.name() == "request_id" {
+                    self.0.push(String::from(value));
+                }
+            }
+
+            fn record_debug(&mut self, _: &tracing::field::Field, _: &dyn fmt::Debug) {}
+        }
+
+        #[cfg(feature = "tracing")]
+        impl tracing::Subscriber for Spans {
+            fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+                return true;
+            }
+
+            fn new_span(&self, attributes: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+                attributes.record(&mut RequestIdVisitor(&mut self.0.lock().unwrap()));
+
+                return tracing::span::Id::from_u64(1u64);
+            }
+
+            fn record(&self, _: &tracing::span::Id, _: &tracing::span::Record<'_>) {}
+
+            fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+
+            fn event(&self, _: &tracing::Event<'_>) {}
+
+            fn enter(&self, _: &tracing::span::Id) {}
+
+            fn exit(&self, _: &tracing::span::Id) {}
+        }
+
+        #[cfg(feature = "tracing")]
+        #[test]
+        fn request_span() {
+            let server = Server::new();
+            let request_ids = Arc::new(Mutex::new(Vec::new()));
+
+            let response = tracing::subscriber::with_default(Spans(request_ids.clone()), || exchange(&server, "GET / HTTP/1.1\r\n\r\n"));
+
+            let request_id = response.lines().find_map(|line| line.strip_prefix("X-Request-Id: ")).unwrap();
+
+            assert_eq!(*request_ids.lock().unwrap(), vec![String::from(request_id)]);
+        }
+    }
+}
@@ -4,7 +4,7 @@ macro_rules! declare_status {
     ($({$enum_name: ident, $code: expr, $code_text: expr, $text: expr, $phrase: expr}),+) => {
         // HTTP status code as registered with the Internet Assigned Numbers Authority (https://www.iana.org).
         // See https://www.iana.org/assignments/http-status-codes/http-status-codes.xhtml.
-        #[derive(PartialEq, Eq)]
+        #[derive(PartialEq, Eq, Clone, Copy)]
         pub enum Status {
             $($enum_name,)+
         }